@@ -1,15 +1,70 @@
 use crate::datastore::{ 
     Datastore, 
-    Weapon, WeaponRange, Armor, 
+    Weapon, Armor, 
     Race, Subrace, 
     Class, 
     Spell, 
-    SpellLevel, SpellSlots,
-    Skill, SkillLevel, CombatProficiency,
+    SpellLevel, SpellSlots, SpellCaster, SpellProgression,
+    Skill, SkillLevel, Rounding, CombatProficiency,
+    FeatureEffect,
     Feat, FeatEffect, FeatPrerequisite,
+    Advancement,
+    MagicEffect,
 };
+use crate::error::{ Error, DataKind };
 use std::collections::{ HashMap, HashSet };
 
+mod history;
+pub use history::{ Edit, History };
+
+mod level_up;
+#[allow(unused_imports)]
+pub use level_up::{ LevelUp, Decision, Choice, HitPointChoice, Improvement };
+use level_up::LevelUpRecord;
+
+mod inventory;
+pub use inventory::{ Inventory, InventoryEntry, ItemKind, EntryId, Currency, ATTUNEMENT_SLOTS };
+
+mod encumbrance;
+#[allow(unused_imports)]
+pub use encumbrance::{ EncumbranceRules, Encumbrance };
+
+mod magic_items;
+
+mod starting_equipment;
+pub use starting_equipment::EquipmentChoice;
+
+mod shop;
+#[allow(unused_imports)]
+pub use shop::Shop;
+
+mod conditions;
+pub use conditions::{ Duration, RollMode, ActiveCondition };
+
+mod tools;
+
+mod traits;
+
+mod movement;
+
+mod damage;
+
+mod senses;
+#[allow(unused_imports)]
+pub use senses::Senses;
+
+mod attacks;
+#[allow(unused_imports)]
+pub use attacks::{ Attack, AttackNote };
+
+mod saves;
+#[allow(unused_imports)]
+pub use saves::SaveSummary;
+
+mod rolls;
+#[allow(unused_imports)]
+pub use rolls::{ Roller, SimpleRoller, RollContext, RollResult, Critical };
+
 pub struct Character<'d> {
     pub name : String,
     data : &'d Datastore,
@@ -17,12 +72,60 @@ pub struct Character<'d> {
     abilities : Abilities,
     race : String,
     subrace : String,
-    class : String,
+    classes : Vec<ClassLevel>,
+    level_ups : Vec<LevelUpRecord>,
     languages : HashSet<String>,
     feats : HashSet<String>,
     skills : HashMap<Skill, SkillLevel>,
     combat_proficiencies : HashSet<CombatProficiency>,
     known_spells : KnownSpells,
+    used_spell_slots : SpellSlots,
+    used_short_rest_spell_slots : SpellSlots,
+    advancement : Advancement,
+    experience : u32,
+    milestone_level : Level,
+    inventory : Inventory,
+    encumbrance_rules : EncumbranceRules,
+    background : String,
+    starting_equipment_taken : bool,
+    conditions : Vec<ActiveCondition>,
+    exhaustion : u8,
+    tools : HashMap<String, SkillLevel>,
+    chosen_tools : Vec<String>,
+    used_innate_spells : HashMap<String, u8>,
+    hit_points_lost : u16,
+    temporary_hit_points : u16,
+}
+
+/// Where a proficiency of a character comes from
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum ProficiencySource {
+    /// Set on the character directly
+    Character,
+    Race(String),
+    Subrace(String),
+    Class(String),
+    Feat(String),
+    /// A class or subclass feature
+    Feature(String),
+    /// An equipped magic item
+    Item(String),
+}
+
+/// A row of the skills table on the character sheet
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SkillSummary {
+    pub skill : Skill,
+    pub ability : Ability,
+    pub level : SkillLevel,
+    pub modifier : Modifier,
+}
+
+/// The levels a character has in one of their classes
+struct ClassLevel {
+    class : String,
+    level : Level,
+    subclass : Option<String>,
 }
 
 #[derive(PartialEq, Eq, Hash)]
@@ -49,38 +152,74 @@ impl<'d> Character<'d> {
             abilities : Abilities::new(),
             race : String::new(),
             subrace : String::new(),
-            class : String::new(),
+            classes : Vec::new(),
+            level_ups : Vec::new(),
             languages : HashSet::new(),
             feats : HashSet::new(),
             skills : HashMap::new(),
             combat_proficiencies : HashSet::new(),
             known_spells : Vec::new(),
+            used_spell_slots : SpellSlots::new(),
+            used_short_rest_spell_slots : SpellSlots::new(),
+            advancement : Advancement::Experience,
+            experience : 0,
+            milestone_level : 1,
+            inventory : Inventory::new(),
+            encumbrance_rules : EncumbranceRules::Standard,
+            background : String::new(),
+            starting_equipment_taken : false,
+            conditions : Vec::new(),
+            exhaustion : 0,
+            tools : HashMap::new(),
+            chosen_tools : Vec::new(),
+            used_innate_spells : HashMap::new(),
+            hit_points_lost : 0,
+            temporary_hit_points : 0,
         }
     }
-    /// Returns the current ability score of the character for the ability
+    /// Returns the current ability score of the character for the ability,
+    /// unless a magic item sets it to a higher score
     pub fn ability(&self, ability : &Ability) -> &AbilityScore {
-        self.abilities.get(ability)
+        self.magic_effects().into_iter()
+            .filter_map( |effect| match effect {
+                MagicEffect::AbilityOverride(overridden, score) if overridden == ability => Some(score),
+                _ => None,
+            })
+            .fold(self.abilities.get(ability), |highest, score| if score > highest { score } else { highest } )
     }
     /// Returns the current size of the character, or throws an error if they have no race determining their size
-    pub fn size(&self) -> Result<&Size, String> {
-        match self.data.get_race(&self.race) {
-            Some(r) => Ok(&r.size),
-            None => Err("Character has no race or race was not found.".to_owned())
-        }
+    pub fn size(&self) -> Result<&Size, Error> {
+        Ok(&self.race_data()?.size)
     }
+    /// Returns the hit die of the first class of the character
     pub fn hit_die(&self) -> Option<&Die> {
-        match self.data.get_class(&self.class) {
+        match self.primary_class() {
             Some(class) => Some(&class.hit_die),
             None => None,
         }
     }
-    pub fn saving_throw(&self, ability : &Ability) -> Modifier {
-        Ability::score_to_mod(self.ability(ability))
-        +
-        match self.data.get_class(&self.class) {
-            Some(class) => if class.saving_throws.contains(ability) { self.proficiency_bonus() } else { 0 },
-            None => 0,
-        }
+    /// Returns the classes of the character together with their level in each, starting with their first class
+    pub fn class_levels(&self) -> Vec<(&str, Level)> {
+        self.classes.iter().map( |class| (class.class.as_str(), class.level) ).collect()
+    }
+    /// Returns the subclass the character has chosen for one of their classes
+    pub fn subclass(&self, class : &str) -> Option<&str> {
+        self.classes.iter()
+            .find( |class_level| class_level.class == class )
+            .and_then( |class_level| class_level.subclass.as_deref() )
+    }
+    /// Returns the level of the character in the specified class, which is 0 if they have no levels in it
+    pub fn class_level(&self, class : &str) -> Level {
+        self.classes.iter()
+            .find( |class_level| class_level.class == class )
+            .map_or(0, |class_level| class_level.level )
+    }
+    pub fn level(&self) -> Level {
+        self.level
+    }
+    /// The first class of the character determines e.g. their saving throw proficiencies
+    fn primary_class(&self) -> Option<&'d Class> {
+        self.classes.first().and_then( |class| self.data.get_class(&class.class) )
     }
     /// Endows the character with the ability to speak the specified language
     pub fn learn_language(&mut self, language : String) {
@@ -103,43 +242,105 @@ impl<'d> Character<'d> {
     pub fn add_combat_proficiency(&mut self, prof : CombatProficiency) {
         self.combat_proficiencies.insert(prof);
     }
-    /// Returns the attack modifier of the character with the specified weapon,
-    /// taking into account proficiencies
+    /// Returns the attack modifier of the character with a mundane copy of the specified weapon,
+    /// taking into account proficiencies. Use `entry_attack_mod` for weapons in the inventory, which may be magic.
     pub fn get_attack_mod(&self, weapon : &Weapon) -> Modifier {
-        let ability_mod = match weapon.range_category {
-            WeaponRange::Melee => Ability::score_to_mod(self.ability(&Ability::Str)),
-            WeaponRange::Ranged => Ability::score_to_mod(self.ability(&Ability::Dex)),
-        };
-        ability_mod
+        Ability::score_to_mod(self.ability(&self.weapon_ability(weapon)))
         + // Proficiency bonus
         if self.proficient_with_weapon(weapon) { self.proficiency_bonus() } else { 0 }
+        +
+        self.condition_attack_modifier()
     }
-    pub fn skill_mod(&self, ability : &Ability, skill : &Skill) -> Modifier {
-        Ability::score_to_mod(self.ability(ability))
+    /// Returns the modifier for a check with the skill using its default ability
+    pub fn skill_mod_default(&self, skill : &Skill) -> Result<Modifier, Error> {
+        self.skill_mod(&skill.default_ability(), skill)
+    }
+    /// Returns the modifier for a check with the skill using any ability, e.g. for Strength (Intimidation) checks
+    pub fn skill_mod(&self, ability : &Ability, skill : &Skill) -> Result<Modifier, Error> {
+        Ok(Ability::score_to_mod(self.ability(ability))
         +
-        match self.skill_level(skill) {
-            SkillLevel::None => 0,
-            SkillLevel::Proficient => self.proficiency_bonus(),
-            SkillLevel::Expert => 2 * self.proficiency_bonus(),
-        }
+        self.proficiency_modifier(&self.skill_level(skill)?)
+        +
+        self.magic_effects().into_iter()
+            .filter_map( |effect| match effect {
+                MagicEffect::SkillBonus(bonus_skill, bonus) if bonus_skill == skill => Some(*bonus),
+                _ => None,
+            })
+            .sum::<Modifier>()
+        +
+        self.condition_check_modifier())
     }
-    pub fn set_level(&mut self, level : Level) {
+    /// Switches between gaining levels through experience points and through milestones
+    pub fn set_advancement(&mut self, advancement : Advancement) {
+        self.advancement = advancement;
+        self.catch_up_milestones();
+    }
+    pub fn experience(&self) -> u32 {
+        self.experience
+    }
+    /// Awards experience points to the character and returns how many levels they can gain now
+    pub fn award_xp(&mut self, experience : u32) -> Result<Level, Error> {
+        if self.advancement != Advancement::Experience {
+            return Err(Error::Validation("Character does not gain levels through experience points.".to_owned()));
+        }
+        self.experience = self.experience.saturating_add(experience);
+        Ok(self.available_levels())
+    }
+    /// Awards a milestone, which allows the character to gain one more level, and returns how many levels they can gain now.
+    /// Milestones beyond the highest level of the experience table are not awarded.
+    pub fn award_milestone(&mut self) -> Result<Level, Error> {
+        if self.advancement != Advancement::Milestone {
+            return Err(Error::Validation("Character does not gain levels through milestones.".to_owned()));
+        }
+        if self.milestone_level as usize >= self.data.experience_table().thresholds.len() {
+            return Err(Error::InvalidLevel(self.milestone_level.saturating_add(1)));
+        }
+        self.milestone_level += 1;
+        Ok(self.available_levels())
+    }
+    /// Returns how many levels the character can gain with their experience points or milestones
+    pub fn available_levels(&self) -> Level {
+        let reached_level = match self.advancement {
+            Advancement::Experience => self.data.experience_table().level_for(self.experience),
+            Advancement::Milestone => self.milestone_level,
+        };
+        std::cmp::max(0, reached_level - self.level)
+    }
+    /// Sets the total level of the character. If they have several classes,
+    /// the difference is made up by their first class. Levels gained through level ups
+    /// cannot be taken away this way, they have to be reverted with `level_down`.
+    pub fn set_level(&mut self, level : Level) -> Result<(), Error> {
+        let leveled_up_to = self.leveled_up_to();
+        if level < leveled_up_to {
+            return Err(Error::Validation(format!("Character gained level {} through a level up, which has to be reverted first.", leveled_up_to)));
+        }
+        if let Some(class) = self.classes.first_mut() {
+            class.level = std::cmp::max(1, class.level + level - self.level);
+        }
         self.level = level;
+        self.catch_up_milestones();
+        Ok(())
+    }
+    /// Makes sure the level reached through milestones is never below the level of the character,
+    /// so that every milestone awarded from now on allows one more level
+    fn catch_up_milestones(&mut self) {
+        self.milestone_level = std::cmp::max(self.milestone_level, self.level);
     }
-    pub fn learn_feat(&mut self, feat : &Feat) -> Result<(), String> {
+    pub fn learn_feat(&mut self, feat : &Feat) -> Result<(), Error> {
         if self.meets_prerequisites(feat) {
             self.feats.insert(feat.name.clone());
             for effect in &feat.effects {
                 match effect {
                     FeatEffect::AbilityIncrease(ability, increase) => { 
-                        *self.abilities.get_mut(&ability) = *self.ability(&ability) + increase; 
+                        *self.abilities.get_mut(ability) += increase; 
                     },
-                    FeatEffect::SkillProficiency(_) => (),
+                    FeatEffect::SkillProficiency(_) | FeatEffect::Advantage(_) | FeatEffect::Save(_) | FeatEffect::Speed(_)
+                        | FeatEffect::InitiativeBonus(_) | FeatEffect::CannotBeSurprised => (),
                 }    
             };
             Ok(())
         } else {
-            Err("This character does not meet the prerequisites for this feat.".to_owned())
+            Err(Error::PrerequisitesNotMet { feat : feat.name.clone() })
         }
     }
     fn meets_prerequisites(&self, feat : &Feat) -> bool {
@@ -156,9 +357,10 @@ impl<'d> Character<'d> {
             for effect in &feat.effects {
                 match effect {
                     FeatEffect::AbilityIncrease(ability, increase) => { 
-                        *self.abilities.get_mut(&ability) = *self.ability(&ability) - increase; 
+                        *self.abilities.get_mut(ability) -= increase; 
                     },
-                    FeatEffect::SkillProficiency(_) => (),
+                    FeatEffect::SkillProficiency(_) | FeatEffect::Advantage(_) | FeatEffect::Save(_) | FeatEffect::Speed(_)
+                        | FeatEffect::InitiativeBonus(_) | FeatEffect::CannotBeSurprised => (),
                 }    
             };        
         }
@@ -170,11 +372,48 @@ impl<'d> Character<'d> {
     pub fn proficiency_bonus(&self) -> Modifier { 
         2 + ( (self.level - 1) / 4 )
     }
-    /// Returns the current speed of the character, or throws an error if they have no race determining their base speed
-    pub fn speed(&self) -> Result<&Speed, String> {
+    /// Returns how much of the proficiency bonus is added for the skill level
+    pub fn proficiency_modifier(&self, level : &SkillLevel) -> Modifier {
+        match level {
+            SkillLevel::None => 0,
+            SkillLevel::HalfProficient(Rounding::Down) => self.proficiency_bonus() / 2,
+            SkillLevel::HalfProficient(Rounding::Up) => ( self.proficiency_bonus() + 1 ) / 2,
+            SkillLevel::Proficient => self.proficiency_bonus(),
+            SkillLevel::Expert => 2 * self.proficiency_bonus(),
+        }
+    }
+    /// Returns the modifier for an ability check that does not involve a skill or tool
+    pub fn ability_check_mod(&self, ability : &Ability) -> Modifier {
+        Ability::score_to_mod(self.ability(ability))
+        +
+        self.proficiency_modifier(&self.half_proficiency())
+        +
+        self.condition_check_modifier()
+    }
+    /// Initiative is a dexterity check, which feats like Alert can add to
+    pub fn initiative(&self) -> Modifier {
+        self.ability_check_mod(&Ability::Dex) + self.feat_initiative_bonus()
+    }
+    /// Returns the skill level class features like Jack of All Trades grant for all ability checks,
+    /// which is None if the character has no such feature
+    fn half_proficiency(&self) -> SkillLevel {
+        self.features().into_iter()
+            .flat_map( |feature| feature.effects.iter() )
+            .filter_map( |effect| match effect {
+                FeatureEffect::HalfProficiency(rounding) => Some(SkillLevel::HalfProficient(*rounding)),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(SkillLevel::None)
+    }
+    /// Looks up the race of the character in the datastore
+    fn race_data(&self) -> Result<&'d Race, Error> {
+        if self.race.is_empty() {
+            return Err(Error::NotChosen(DataKind::Race));
+        }
         match self.data.get_race(&self.race) {
-            Some(r) => Ok(&r.speed),
-            None => Err("Character has no race or race was not found.".to_owned())
+            Some(r) => Ok(r),
+            None => Err(Error::missing(DataKind::Race, &self.race)),
         }
     }
     /// Returns true if the character speaks the specified language
@@ -185,54 +424,147 @@ impl<'d> Character<'d> {
     pub fn can_equip(&self, armor : &Armor) -> bool {
         self.combat_proficiencies.contains(&CombatProficiency::ArmorCategory(armor.category))
     }
-    /// Returns the skill level of the character in the specified skill,
-    /// taking into account proficiencies from themself as well as from their race
-    pub fn skill_level(&self, skill : &Skill) -> &SkillLevel {
-        let own_skill_level = match self.skills.get(skill) {
-            Some(prof) => prof,
-            None => &SkillLevel::None,
-        };
-        if *own_skill_level == SkillLevel::None {
-            let race_skill_level = match self.data.get_race(&self.race) {
-                Some(r) =>  if r.skill_proficiencies.contains(skill) { 
-                                &SkillLevel::Proficient 
-                            } else { match r.get_subrace(&self.subrace) {
-                                Some(sr) => if sr.skill_proficiencies.contains(skill) { &SkillLevel::Proficient } else { &SkillLevel::None },
-                                None => &SkillLevel::None,
-                            } },
-                None => &SkillLevel::None,
+    /// Returns every skill with its default ability, the skill level of the character and their modifier
+    pub fn skill_table(&self) -> Result<Vec<SkillSummary>, Error> {
+        Skill::skills().into_iter().map( |skill| Ok(SkillSummary {
+            ability : skill.default_ability(),
+            level : self.skill_level(&skill)?,
+            modifier : self.skill_mod_default(&skill)?,
+            skill,
+        })).collect()
+    }
+    /// Returns the highest skill level the character has in the specified skill from any source
+    pub fn skill_level(&self, skill : &Skill) -> Result<SkillLevel, Error> {
+        Ok(self.skill_sources(skill)?.into_iter()
+            .map( |(_, level)| level )
+            .max()
+            .unwrap_or(SkillLevel::None))
+    }
+    /// Returns every source that grants the character a skill level in the specified skill: themself, their race
+    /// and subrace, their first class, their feats and class features like Expertise or Jack of All Trades
+    pub fn skill_sources(&self, skill : &Skill) -> Result<Vec<(ProficiencySource, SkillLevel)>, Error> {
+        let mut sources = Vec::new();
+        if let Some(level) = self.skills.get(skill) {
+            if *level != SkillLevel::None {
+                sources.push((ProficiencySource::Character, *level));
+            }
+        }
+        if let Some(race) = self.data.get_race(&self.race) {
+            if race.skill_proficiencies.contains(skill) {
+                sources.push((ProficiencySource::Race(race.name.clone()), SkillLevel::Proficient));
+            }
+            if let Some(subrace) = race.get_subrace(&self.subrace) {
+                if subrace.skill_proficiencies.contains(skill) {
+                    sources.push((ProficiencySource::Subrace(subrace.name.clone()), SkillLevel::Proficient));
+                }
+            }
+        }
+        if let Some(class) = self.primary_class() {
+            if class.skill_proficiencies.contains(skill) {
+                sources.push((ProficiencySource::Class(class.name.clone()), SkillLevel::Proficient));
+            }
+        }
+        for feat in &self.feats {
+            let feat_data = match self.data.get_feat(feat) {
+                Some(feat_data) => feat_data,
+                None => { return Err(Error::missing(DataKind::Feat, feat)); },
             };
-            if *race_skill_level == SkillLevel::None {
-                let class_skill_level = match self.data.get_class(&self.class) {
-                    Some(class) => if class.skill_proficiencies.contains(skill) { &SkillLevel::Proficient } else { &SkillLevel::None },
-                    None => &SkillLevel::None,
+            if feat_data.effects.contains(&FeatEffect::SkillProficiency(skill.clone())) {
+                sources.push((ProficiencySource::Feat(feat.clone()), SkillLevel::Proficient));
+            }
+        }
+        for feature in self.features() {
+            for effect in &feature.effects {
+                let level = match effect {
+                    FeatureEffect::SkillProficiency(granted) if granted == skill => SkillLevel::Proficient,
+                    FeatureEffect::Expertise(granted) if granted == skill => SkillLevel::Expert,
+                    FeatureEffect::HalfProficiency(rounding) => SkillLevel::HalfProficient(*rounding),
+                    _ => { continue; },
                 };
-                if *class_skill_level == SkillLevel::None {
-                    let mut feat_skill_level = &SkillLevel::None;
-                    for feat in &self.feats {
-                        let feat_data = self.data.get_feat(feat).unwrap();
-                        if feat_data.effects.contains(&FeatEffect::SkillProficiency(skill.clone())) {
-                            feat_skill_level = &SkillLevel::Proficient;
-                            break;
-                        } else { };
-                    }
-                    feat_skill_level
-                } else {
-                    class_skill_level
+                sources.push((ProficiencySource::Feature(feature.name.clone()), level));
+            }
+        }
+        Ok(sources)
+    }
+    /// Returns the spell slots of the character that are recovered on a long rest
+    pub fn spell_slots(&self) -> Result<SpellSlots, Error> {
+        let shared = match self.data.get_spell_progression(&SpellCaster::Full) {
+            Some(progression) => progression,
+            None => { return Err(Error::missing(DataKind::SpellProgression, "Full")); },
+        };
+        SpellProgression::multiclass_slots(&self.caster_levels()?, shared)
+    }
+    /// Returns the spell slots of the character that are recovered on a short rest
+    pub fn short_rest_spell_slots(&self) -> Result<SpellSlots, Error> {
+        SpellProgression::multiclass_short_rest_slots(&self.caster_levels()?)
+    }
+    /// Returns the levels the character has in each of their spellcasting classes together with the slots of the class
+    fn caster_levels(&self) -> Result<Vec<(Level, &SpellProgression)>, Error> {
+        let mut caster_levels = Vec::new();
+        for class_level in &self.classes {
+            let class = match self.data.get_class(&class_level.class) {
+                Some(class) => class,
+                None => { return Err(Error::missing(DataKind::Class, &class_level.class)); },
+            };
+            if class.spell_caster != SpellCaster::None {
+                match self.data.get_spell_progression(&class.spell_caster) {
+                    Some(progression) => caster_levels.push((class_level.level, progression)),
+                    None => { return Err(Error::missing(DataKind::SpellProgression, &format!("{:?}", class.spell_caster))); },
                 }
-            } else {
-                race_skill_level
-            }         
+            }
+        }
+        Ok(caster_levels)
+    }
+    /// Returns the spell slots the character has not expended since their last long rest
+    pub fn remaining_spell_slots(&self) -> Result<SpellSlots, Error> {
+        Ok(Character::remaining_slots(&self.spell_slots()?, &self.used_spell_slots))
+    }
+    /// Returns the short rest spell slots the character has not expended since their last rest
+    pub fn remaining_short_rest_spell_slots(&self) -> Result<SpellSlots, Error> {
+        Ok(Character::remaining_slots(&self.short_rest_spell_slots()?, &self.used_short_rest_spell_slots))
+    }
+    /// Casts a known spell using a slot of the specified level, which may be higher than the level
+    /// of the spell itself. Regular slots are expended before short rest slots.
+    /// Cantrips can be cast at will and do not use up any slots.
+    pub fn cast_spell(&mut self, spell : &Spell, level : SpellLevel) -> Result<(), Error> {
+        if !self.known_spells.iter().any( |known_spell| known_spell.name == spell.name ) {
+            return Err(Error::Validation(format!("Character does not know the spell \"{}\".", spell.name)));
+        }
+        if level < spell.level || ( level == SpellLevel::Cantrip && spell.level != SpellLevel::Cantrip ) {
+            return Err(Error::Validation("Spells cannot be cast with a slot below their own level.".to_owned()));
+        }
+        if level == SpellLevel::Cantrip {
+            return Ok(());
+        }
+        if self.remaining_spell_slots()?.get(&level).cloned().unwrap_or(0) > 0 {
+            *self.used_spell_slots.entry(level).or_insert(0) += 1;
+            Ok(())
+        } else if self.remaining_short_rest_spell_slots()?.get(&level).cloned().unwrap_or(0) > 0 {
+            *self.used_short_rest_spell_slots.entry(level).or_insert(0) += 1;
+            Ok(())
         } else {
-            own_skill_level
+            Err(Error::NoSpellSlot(level))
         }
     }
-    pub fn spell_slots(&self) -> SpellSlots {
-        let no_slots = SpellLevel::slots(0,0,0,0,0,0,0,0,0);
-        match self.data.get_class(&self.class) {
-            Some(class) => SpellLevel::slots_for_level(&self.level, &class.spell_caster),
-            None => no_slots,
-        }
+    /// Finishes a short rest, recovering all short rest spell slots
+    pub fn short_rest(&mut self) {
+        self.used_short_rest_spell_slots.clear();
+    }
+    /// Finishes a long rest, recovering all hit points, spell slots and innate spells, ending temporary hit points
+    /// and conditions that last until then and removing one level of exhaustion
+    pub fn long_rest(&mut self) {
+        self.hit_points_lost = 0;
+        self.temporary_hit_points = 0;
+        self.used_spell_slots.clear();
+        self.used_innate_spells.clear();
+        self.conditions.retain( |active| active.duration() != Duration::UntilLongRest );
+        self.reduce_exhaustion();
+        self.short_rest();
+    }
+    fn remaining_slots(maximum : &SpellSlots, used : &SpellSlots) -> SpellSlots {
+        maximum.iter()
+            .map( |(level, slots)| (*level, slots.saturating_sub(used.get(level).cloned().unwrap_or(0))) )
+            .collect()
     }
     /// Sets the skill level of a character in a skill independently of race or class
     pub fn set_skill_level(&mut self, skill : &Skill, level : SkillLevel) {
@@ -243,7 +575,8 @@ impl<'d> Character<'d> {
         self.abilities.set(&ability, score);
     }
     /// Sets the race of the character to the specified score and removes all bonuses of their old race
-    pub fn set_race(&mut self, race : &Race) -> Result<(),String> {
+    pub fn set_race(&mut self, race : &Race) -> Result<(), Error> {
+        self.unset_subrace()?;
         self.unset_race()?;
         for (attr, bonus) in race.ability_bonuses.iter() {
             self.set_ability(attr, *self.abilities.get(attr) + *bonus);
@@ -254,7 +587,11 @@ impl<'d> Character<'d> {
         self.race = race.name.to_owned();
         Ok(())
     }
-    pub fn set_subrace(&mut self, subrace : &Subrace) -> Result<(), String> {
+    /// Sets the subrace of the character, which must be one of the subraces of their race
+    pub fn set_subrace(&mut self, subrace : &Subrace) -> Result<(), Error> {
+        if self.race_data()?.get_subrace(&subrace.name).is_none() {
+            return Err(Error::missing(DataKind::Subrace, &subrace.name));
+        }
         self.unset_subrace()?;
         for (attr, bonus) in subrace.ability_bonuses.iter() {
             self.set_ability(attr, *self.abilities.get(attr) + *bonus);
@@ -265,14 +602,20 @@ impl<'d> Character<'d> {
         self.subrace = subrace.name.to_owned();
         Ok(())        
     }
-    pub fn set_class(&mut self, class : &Class) {
-        self.class = class.name.to_owned();
+    /// Makes the specified class the only class of the character, with all of their levels in it.
+    /// This is not possible once the character has gained levels through level ups.
+    pub fn set_class(&mut self, class : &Class) -> Result<(), Error> {
+        if !self.level_ups.is_empty() {
+            return Err(Error::Validation("Character has gained levels through level ups, which have to be reverted first.".to_owned()));
+        }
+        self.classes = vec![ClassLevel { class : class.name.to_owned(), level : self.level, subclass : None }];
+        Ok(())
     }
-    fn unset_subrace(&mut self) -> Result<(), String> {
+    fn unset_subrace(&mut self) -> Result<(), Error> {
         if self.subrace != "" {
-            let old_subrace = match self.data.get_race(&self.race).unwrap().get_subrace(&self.subrace) {
+            let old_subrace = match self.race_data()?.get_subrace(&self.subrace) {
                 Some(r) => r,
-                None => { return Err("Subrace not found!".to_owned()); },
+                None => { return Err(Error::missing(DataKind::Subrace, &self.subrace)); },
             };
             for (attr, bonus) in old_subrace.ability_bonuses.iter() {
                 self.set_ability(attr, *self.abilities.get(attr) - *bonus);
@@ -285,12 +628,9 @@ impl<'d> Character<'d> {
         Ok(())
     }
     /// Undo the effects of the current race
-    fn unset_race(&mut self) -> Result<(), String> {
+    fn unset_race(&mut self) -> Result<(), Error> {
         if self.race != "" {
-            let old_race = match self.data.get_race(&self.race) {
-                Some(r) => r,
-                None => { return Err("Old race not found!".to_owned()) }
-            };
+            let old_race = self.race_data()?;
             for (attr, bonus) in old_race.ability_bonuses.iter() {
                 self.set_ability(attr, *self.abilities.get(attr) - *bonus);
             } 
@@ -317,11 +657,11 @@ impl<'d> Character<'d> {
                             } },
             None => false,
         } ||
-        match self.data.get_class(&self.class) {
+        self.classes.iter().any( |class_level| match self.data.get_class(&class_level.class) {
             Some(class) => { class.combat_proficiencies.contains(&CombatProficiency::WeaponCategory(weapon.category)) ||
                              class.combat_proficiencies.contains(&CombatProficiency::Weapon(weapon.name.clone())) },
             None => false,
-        }
+        })
     }
 }
 
@@ -329,6 +669,22 @@ impl<'d> Character<'d> {
 pub enum Ability { Str, Dex, Con, Wis, Int, Cha }
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Die { D4, D6, D8, D10, D12, D20 }
+impl Die {
+    pub fn sides(&self) -> u8 {
+        match self {
+            Die::D4 => 4,
+            Die::D6 => 6,
+            Die::D8 => 8,
+            Die::D10 => 10,
+            Die::D12 => 12,
+            Die::D20 => 20,
+        }
+    }
+    /// Returns the fixed value that can be taken instead of rolling the die, e.g. for hit points
+    pub fn average(&self) -> u8 {
+        self.sides() / 2 + 1
+    }
+}
 pub type AbilityScore = i8; //Not unsigned because otherwise mismatching types make computing the ability modifier hell
 type Modifier = i8;
 type Level = i8;
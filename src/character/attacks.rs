@@ -0,0 +1,167 @@
+use super::{ Character, Ability, Modifier, Die, InventoryEntry };
+use crate::datastore::{ Weapon, WeaponRange, WeaponProperty, Reach, ArmorCategory, DamageType, SpellLevel };
+use crate::error::Error;
+
+/// Reach of unarmed strikes and natural weapons in feet
+const MELEE_REACH : Reach = 5;
+/// Character levels at which cantrips roll another damage die
+const CANTRIP_DIE_LEVELS : [i8; 3] = [5, 11, 17];
+
+/// A row of the attacks table on the character sheet
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Attack {
+    pub name : String,
+    pub to_hit : Modifier,
+    /// The number of damage dice, which is 0 for flat damage like that of an unarmed strike
+    pub damage_dice : u8,
+    pub damage_die : Die,
+    pub damage_modifier : Modifier,
+    pub damage_type : DamageType,
+    /// The reach of melee attacks or the range of ranged attacks in feet
+    pub range : Reach,
+    pub notes : Vec<AttackNote>,
+}
+
+/// Remarks on how an attack is made, shown next to it on the character sheet
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum AttackNote {
+    /// The attack can use dexterity instead of strength
+    Finesse,
+    /// The weapon can also be thrown
+    Thrown,
+    /// A versatile weapon wielded with both hands
+    TwoHanded,
+    /// A bonus action attack with a light weapon in the other hand, which adds no positive modifier to the damage
+    OffHand,
+    /// The character is not proficient with the weapon, so the proficiency bonus is not added
+    NotProficient,
+    /// An attack made by casting a cantrip
+    Cantrip,
+}
+
+impl<'d> Character<'d> {
+    /// Returns all attacks the character can make: those with their equipped weapons, in both grips
+    /// for versatile weapons and with the other hand when fighting with two light weapons, their
+    /// unarmed strike and natural weapons and the attacks of the cantrips they know
+    pub fn attacks(&self) -> Result<Vec<Attack>, Error> {
+        let mut weapons = Vec::new();
+        let mut shield = false;
+        for (_, entry) in self.inventory.equipped() {
+            if let Some(weapon) = self.entry_weapon(entry)? {
+                weapons.push((entry, weapon));
+            }
+            if let Some(armor) = self.entry_armor(entry)? {
+                shield = shield || armor.category == ArmorCategory::Shield;
+            }
+        }
+        let hand_free = !shield && weapons.len() == 1;
+        let mut attacks = Vec::new();
+        let mut light_weapons = 0;
+        for (entry, weapon) in weapons {
+            let attack = self.weapon_attack(entry, weapon);
+            attacks.push(attack.clone());
+            for property in &weapon.properties {
+                match property {
+                    WeaponProperty::Versatile(die) if hand_free => {
+                        let mut two_handed = attack.clone();
+                        two_handed.damage_die = *die;
+                        two_handed.notes.push(AttackNote::TwoHanded);
+                        attacks.push(two_handed);
+                    },
+                    WeaponProperty::Light if weapon.range_category == WeaponRange::Melee => {
+                        light_weapons += 1;
+                        if light_weapons > 1 {
+                            let mut off_hand = attack.clone();
+                            off_hand.damage_modifier = std::cmp::min(off_hand.damage_modifier, self.entry_weapon_bonus(entry));
+                            off_hand.notes.push(AttackNote::OffHand);
+                            attacks.push(off_hand);
+                        }
+                    },
+                    _ => (),
+                }
+            }
+        }
+        let strength = Ability::score_to_mod(self.ability(&Ability::Str));
+        let melee_to_hit = strength + self.proficiency_bonus() + self.condition_attack_modifier();
+        attacks.push(Attack {
+            name : "Unarmed Strike".to_owned(),
+            to_hit : melee_to_hit,
+            damage_dice : 0,
+            damage_die : Die::D4,
+            damage_modifier : 1 + strength,
+            damage_type : DamageType::Bludgeoning,
+            range : MELEE_REACH,
+            notes : Vec::new(),
+        });
+        for weapon in self.natural_weapons() {
+            attacks.push(Attack {
+                name : weapon.name.clone(),
+                to_hit : melee_to_hit,
+                damage_dice : 1,
+                damage_die : weapon.damage_die,
+                damage_modifier : strength,
+                damage_type : weapon.damage_type,
+                range : MELEE_REACH,
+                notes : Vec::new(),
+            });
+        }
+        for known in self.spells() {
+            let spell = match self.data.get_spell(known.name()) {
+                Some(spell) if spell.level == SpellLevel::Cantrip => spell,
+                _ => continue,
+            };
+            if let (Some(attack), Some(damage_type)) = (&spell.attack, spell.damage_type) {
+                attacks.push(Attack {
+                    name : spell.name.clone(),
+                    to_hit : Ability::score_to_mod(self.ability(known.ability())) + self.proficiency_bonus() + self.condition_attack_modifier(),
+                    damage_dice : attack.damage_dice * self.cantrip_dice_multiplier(),
+                    damage_die : attack.damage_die,
+                    damage_modifier : 0,
+                    damage_type,
+                    range : attack.range,
+                    notes : vec![AttackNote::Cantrip],
+                });
+            }
+        }
+        Ok(attacks)
+    }
+    /// Returns the ability used for attacks with the weapon. Finesse weapons use the better of strength and dexterity.
+    pub(super) fn weapon_ability(&self, weapon : &Weapon) -> Ability {
+        let default = match weapon.range_category {
+            WeaponRange::Melee => Ability::Str,
+            WeaponRange::Ranged => Ability::Dex,
+        };
+        if weapon.properties.contains(&WeaponProperty::Finesse) && self.ability(&Ability::Dex) > self.ability(&Ability::Str) {
+            Ability::Dex
+        } else {
+            default
+        }
+    }
+    fn weapon_attack(&self, entry : &InventoryEntry, weapon : &Weapon) -> Attack {
+        let bonus = self.entry_weapon_bonus(entry);
+        let mut notes = Vec::new();
+        for property in &weapon.properties {
+            match property {
+                WeaponProperty::Finesse => notes.push(AttackNote::Finesse),
+                WeaponProperty::Thrown => notes.push(AttackNote::Thrown),
+                _ => (),
+            }
+        }
+        if !self.proficient_with_weapon(weapon) {
+            notes.push(AttackNote::NotProficient);
+        }
+        Attack {
+            name : entry.name().to_owned(),
+            to_hit : self.get_attack_mod(weapon) + bonus,
+            damage_dice : weapon.damage_dice,
+            damage_die : weapon.damage_die,
+            damage_modifier : Ability::score_to_mod(self.ability(&self.weapon_ability(weapon))) + bonus,
+            damage_type : weapon.damage_type,
+            range : weapon.reach,
+            notes,
+        }
+    }
+    fn cantrip_dice_multiplier(&self) -> u8 {
+        1 + CANTRIP_DIE_LEVELS.iter().filter( |level| self.level >= **level ).count() as u8
+    }
+}
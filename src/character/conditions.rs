@@ -0,0 +1,126 @@
+use super::{ Character, Modifier, Speed };
+use crate::datastore::{ Condition, ConditionEffect };
+use crate::error::Error;
+
+/// How long a condition lasts unless it is removed earlier
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Duration { Indefinite, Rounds(u32), UntilLongRest }
+
+/// Whether a d20 is rolled once, or twice keeping the higher or the lower result
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum RollMode { Normal, Advantage, Disadvantage }
+
+/// A condition the character currently suffers from
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ActiveCondition {
+    name : String,
+    duration : Duration,
+}
+
+impl RollMode {
+    /// Advantage and disadvantage cancel each other out, no matter how many sources there are of each
+    pub fn from_sources(advantage : bool, disadvantage : bool) -> Self {
+        match (advantage, disadvantage) {
+            (true, false) => RollMode::Advantage,
+            (false, true) => RollMode::Disadvantage,
+            _ => RollMode::Normal,
+        }
+    }
+}
+
+impl ActiveCondition {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn duration(&self) -> Duration { self.duration }
+}
+
+impl<'d> Character<'d> {
+    /// Makes the character suffer from a condition. If they already do, only the duration is replaced.
+    /// Throws an error if the character is immune to the condition.
+    pub fn add_condition(&mut self, condition : &Condition, duration : Duration) -> Result<(), Error> {
+        if self.condition_immunities().contains(&condition.name.as_str()) {
+            return Err(Error::Validation(format!("Character is immune to being {}.", condition.name)));
+        }
+        match self.conditions.iter_mut().find( |active| active.name == condition.name ) {
+            Some(active) => { active.duration = duration; },
+            None => self.conditions.push(ActiveCondition { name : condition.name.clone(), duration }),
+        }
+        Ok(())
+    }
+    pub fn remove_condition(&mut self, condition : &str) {
+        self.conditions.retain( |active| active.name != condition );
+    }
+    pub fn conditions(&self) -> &[ActiveCondition] {
+        &self.conditions
+    }
+    pub fn has_condition(&self, condition : &str) -> bool {
+        self.conditions.iter().any( |active| active.name == condition )
+    }
+    /// Counts down the durations of the conditions of the character, removing those that ran out
+    pub fn pass_rounds(&mut self, rounds : u32) {
+        for active in &mut self.conditions {
+            if let Duration::Rounds(remaining) = active.duration {
+                active.duration = Duration::Rounds(remaining.saturating_sub(rounds));
+            }
+        }
+        self.conditions.retain( |active| active.duration != Duration::Rounds(0) );
+    }
+    pub fn exhaustion(&self) -> u8 {
+        self.exhaustion
+    }
+    /// Adds a level of exhaustion, or throws an error if the character has reached the last level already
+    pub fn gain_exhaustion(&mut self) -> Result<(), Error> {
+        if self.exhaustion as usize >= self.data.exhaustion().len() {
+            return Err(Error::Validation("Character cannot gain any more levels of exhaustion.".to_owned()));
+        }
+        self.exhaustion += 1;
+        Ok(())
+    }
+    pub fn reduce_exhaustion(&mut self) {
+        self.exhaustion = self.exhaustion.saturating_sub(1);
+    }
+    /// Returns the effects of all conditions and levels of exhaustion the character suffers from
+    pub fn condition_effects(&self) -> Vec<&'d ConditionEffect> {
+        let data = self.data;
+        self.conditions.iter()
+            .filter_map( |active| data.get_condition(&active.name) )
+            .flat_map( |condition| condition.effects.iter() )
+            .chain(data.exhaustion().iter().take(self.exhaustion as usize).flatten())
+            .collect()
+    }
+    pub fn attack_roll_mode(&self) -> RollMode {
+        self.condition_roll_mode(&ConditionEffect::AttackAdvantage, &ConditionEffect::AttackDisadvantage)
+    }
+    /// Returns whether ability checks, including skill checks, are rolled with advantage or disadvantage
+    pub fn check_roll_mode(&self) -> RollMode {
+        self.condition_roll_mode(&ConditionEffect::CheckAdvantage, &ConditionEffect::CheckDisadvantage)
+    }
+    pub fn save_roll_mode(&self) -> RollMode {
+        self.condition_roll_mode(&ConditionEffect::SaveAdvantage, &ConditionEffect::SaveDisadvantage)
+    }
+    pub(super) fn has_condition_effect(&self, effect : &ConditionEffect) -> bool {
+        self.condition_effects().contains(&effect)
+    }
+    pub(super) fn condition_attack_modifier(&self) -> Modifier {
+        self.condition_effects().into_iter()
+            .filter_map( |effect| match effect { ConditionEffect::AttackModifier(modifier) => Some(*modifier), _ => None } )
+            .sum()
+    }
+    pub(super) fn condition_check_modifier(&self) -> Modifier {
+        self.condition_effects().into_iter()
+            .filter_map( |effect| match effect { ConditionEffect::CheckModifier(modifier) => Some(*modifier), _ => None } )
+            .sum()
+    }
+    /// Applies the effects of the conditions of the character to a speed
+    pub(super) fn conditional_speed(&self, speed : Speed) -> Speed {
+        if self.has_condition_effect(&ConditionEffect::SpeedZero) {
+            0
+        } else if self.has_condition_effect(&ConditionEffect::HalveSpeed) {
+            speed / 2
+        } else {
+            speed
+        }
+    }
+    fn condition_roll_mode(&self, advantage : &ConditionEffect, disadvantage : &ConditionEffect) -> RollMode {
+        RollMode::from_sources(self.has_condition_effect(advantage), self.has_condition_effect(disadvantage))
+    }
+}
@@ -0,0 +1,99 @@
+use super::Character;
+use crate::datastore::{ DamageType, DamageDefense, RacialTrait, FeatureEffect, MagicEffect, ConditionEffect };
+use crate::error::Error;
+
+impl<'d> Character<'d> {
+    /// Returns the damage types the character resists, which halves the damage
+    pub fn resistances(&self) -> Vec<DamageType> {
+        self.damage_types_with(DamageDefense::Resistance)
+    }
+    /// Returns the damage types that cannot hurt the character
+    pub fn immunities(&self) -> Vec<DamageType> {
+        self.damage_types_with(DamageDefense::Immunity)
+    }
+    /// Returns the damage types the character is vulnerable to, which doubles the damage
+    pub fn vulnerabilities(&self) -> Vec<DamageType> {
+        self.damage_types_with(DamageDefense::Vulnerability)
+    }
+    /// Returns the damage the character would take from damage of the type. Immunity prevents it,
+    /// otherwise resistance halves and vulnerability doubles it. Several sources of the same defense count as one.
+    pub fn modified_damage(&self, damage : u16, damage_type : &DamageType) -> u16 {
+        let defenses : Vec<DamageDefense> = self.damage_defenses().into_iter()
+            .filter( |(_, defended)| defended == damage_type )
+            .map( |(defense, _)| defense )
+            .collect();
+        if defenses.contains(&DamageDefense::Immunity) {
+            return 0;
+        }
+        let mut damage = damage;
+        if defenses.contains(&DamageDefense::Resistance) {
+            damage /= 2;
+        }
+        if defenses.contains(&DamageDefense::Vulnerability) {
+            damage = damage.saturating_mul(2);
+        }
+        damage
+    }
+    /// Returns the current hit points of the character, or throws an error if they have no class
+    /// determining their maximum
+    pub fn hit_points(&self) -> Result<u16, Error> {
+        Ok(self.max_hit_points()?.saturating_sub(self.hit_points_lost))
+    }
+    pub fn temporary_hit_points(&self) -> u16 {
+        self.temporary_hit_points
+    }
+    /// Deals damage of the type to the character, which is taken from their temporary hit points first.
+    /// Returns the damage actually dealt after resistances, immunities and vulnerabilities.
+    pub fn take_damage(&mut self, damage : u16, damage_type : &DamageType) -> Result<u16, Error> {
+        let maximum = self.max_hit_points()?;
+        let damage = self.modified_damage(damage, damage_type);
+        let absorbed = std::cmp::min(damage, self.temporary_hit_points);
+        self.temporary_hit_points -= absorbed;
+        self.hit_points_lost = std::cmp::min(maximum, self.hit_points_lost.saturating_add(damage - absorbed));
+        Ok(damage)
+    }
+    /// Restores hit points up to the maximum of the character
+    pub fn heal(&mut self, hit_points : u16) {
+        self.hit_points_lost = self.hit_points_lost.saturating_sub(hit_points);
+    }
+    /// Grants temporary hit points, which do not add up. The character keeps whichever is more.
+    pub fn gain_temporary_hit_points(&mut self, hit_points : u16) {
+        self.temporary_hit_points = std::cmp::max(self.temporary_hit_points, hit_points);
+    }
+    /// Collects the damage defenses from race, class features, magic items and conditions
+    fn damage_defenses(&self) -> Vec<(DamageDefense, DamageType)> {
+        let mut defenses = Vec::new();
+        for racial_trait in self.racial_traits() {
+            if let RacialTrait::DamageDefense(defense, damage_type) = racial_trait {
+                defenses.push((*defense, *damage_type));
+            }
+        }
+        for feature in self.features() {
+            for effect in &feature.effects {
+                if let FeatureEffect::DamageDefense(defense, damage_type) = effect {
+                    defenses.push((*defense, *damage_type));
+                }
+            }
+        }
+        for effect in self.magic_effects() {
+            if let MagicEffect::DamageDefense(defense, damage_type) = effect {
+                defenses.push((*defense, *damage_type));
+            }
+        }
+        for effect in self.condition_effects() {
+            if let ConditionEffect::DamageDefense(defense, damage_type) = effect {
+                defenses.push((*defense, *damage_type));
+            }
+        }
+        defenses
+    }
+    fn damage_types_with(&self, defense : DamageDefense) -> Vec<DamageType> {
+        let mut damage_types = Vec::new();
+        for (_, damage_type) in self.damage_defenses().into_iter().filter( |(found, _)| *found == defense ) {
+            if !damage_types.contains(&damage_type) {
+                damage_types.push(damage_type);
+            }
+        }
+        damage_types
+    }
+}
@@ -0,0 +1,80 @@
+use super::{ Character, Ability, Size, Speed };
+use crate::datastore::{ Weight, POUND };
+use crate::error::Error;
+
+/// Whether carrying heavy loads slows characters down before they reach their carrying capacity
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum EncumbranceRules { Standard, Variant }
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub enum Encumbrance {
+    Unencumbered,
+    /// Only used by the variant rules, reduces speed by 10 feet
+    Encumbered,
+    /// Only used by the variant rules, reduces speed by 20 feet
+    HeavilyEncumbered,
+    /// Above the carrying capacity, speed drops to 5 feet
+    OverCapacity,
+    /// Above what the character can push, drag or lift, they cannot move at all
+    Immobile,
+}
+
+/// Speed of a character carrying more than their carrying capacity
+const OVER_CAPACITY_SPEED : Speed = 5;
+
+impl Size {
+    /// Returns how much more or less creatures of this size can carry than medium ones, in halves
+    fn carrying_halves(&self) -> Weight {
+        match self {
+            Size::Tiny => 1,
+            Size::Small | Size::Medium => 2,
+            Size::Large => 4,
+            Size::Huge => 8,
+            Size::Gargantuan => 16,
+        }
+    }
+}
+
+impl<'d> Character<'d> {
+    pub fn set_encumbrance_rules(&mut self, rules : EncumbranceRules) {
+        self.encumbrance_rules = rules;
+    }
+    /// Returns the weight the character can carry, which depends on their strength and size
+    pub fn carrying_capacity(&self) -> Result<Weight, Error> {
+        self.strength_times(15)
+    }
+    /// Returns the weight the character can push, drag or lift
+    pub fn push_drag_lift(&self) -> Result<Weight, Error> {
+        self.strength_times(30)
+    }
+    /// Returns how much the character is slowed down by what they carry
+    pub fn encumbrance(&self) -> Result<Encumbrance, Error> {
+        let carried = self.carried_weight()?;
+        Ok(if carried > self.push_drag_lift()? {
+            Encumbrance::Immobile
+        } else if carried > self.carrying_capacity()? {
+            Encumbrance::OverCapacity
+        } else if self.encumbrance_rules == EncumbranceRules::Variant && carried > self.strength_times(10)? {
+            Encumbrance::HeavilyEncumbered
+        } else if self.encumbrance_rules == EncumbranceRules::Variant && carried > self.strength_times(5)? {
+            Encumbrance::Encumbered
+        } else {
+            Encumbrance::Unencumbered
+        })
+    }
+    /// Applies the effects of encumbrance to a speed
+    pub(super) fn encumbered_speed(&self, encumbrance : Encumbrance, speed : Speed) -> Speed {
+        match encumbrance {
+            Encumbrance::Unencumbered => speed,
+            Encumbrance::Encumbered => speed.saturating_sub(10),
+            Encumbrance::HeavilyEncumbered => speed.saturating_sub(20),
+            Encumbrance::OverCapacity => std::cmp::min(speed, OVER_CAPACITY_SPEED),
+            Encumbrance::Immobile => 0,
+        }
+    }
+    /// Returns the strength score of the character times the factor in pounds, adjusted for their size
+    fn strength_times(&self, factor : Weight) -> Result<Weight, Error> {
+        let strength = std::cmp::max(0, *self.ability(&Ability::Str)) as Weight;
+        Ok(strength * factor * POUND * self.size()?.carrying_halves() / 2)
+    }
+}
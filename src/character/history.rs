@@ -0,0 +1,209 @@
+use super::{ Character, Ability, AbilityScore, Level, LevelUp, Improvement, ItemKind, EntryId, Currency, EncumbranceRules, EquipmentChoice };
+use crate::datastore::{ Datastore, Skill, SkillLevel, CombatProficiency, Advancement };
+use crate::error::{ Error, DataKind };
+
+/// A single edit of a character, which can be recorded and replayed later
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Edit {
+    SetName(String),
+    SetAbility(Ability, AbilityScore),
+    SetLevel(Level),
+    SetRace(String),
+    SetSubrace(String),
+    SetClass(String),
+    LearnLanguage(String),
+    UnlearnLanguage(String),
+    SetSkillLevel(Skill, SkillLevel),
+    SetToolLevel(String, SkillLevel),
+    ChooseTool(String),
+    AddCombatProficiency(CombatProficiency),
+    LearnFeat(String),
+    UnlearnFeat(String),
+    LearnSpell(String, Ability),
+    UnlearnSpell(String, Ability),
+    LevelUp(LevelUp),
+    LevelDown,
+    ReviseImprovement(Level, Improvement),
+    SetAdvancement(Advancement),
+    AwardXp(u32),
+    AwardMilestone,
+    AddItem(ItemKind, String, u32),
+    RemoveItem(EntryId, u32),
+    MoveItem(EntryId, Option<EntryId>),
+    Equip(EntryId),
+    Unequip(EntryId),
+    Attune(EntryId),
+    EndAttunement(EntryId),
+    GainCurrency(Currency),
+    SpendCurrency(Currency),
+    /// Buys the items for the price
+    Buy(ItemKind, String, u32, Currency),
+    /// Sells items of the entry for the payment
+    Sell(EntryId, u32, Currency),
+    SetEncumbranceRules(EncumbranceRules),
+    SetBackground(String),
+    TakeStartingEquipment(EquipmentChoice, Option<EquipmentChoice>),
+}
+
+/// The log of all edits made to a character, which allows undoing and redoing them
+pub struct History {
+    done : Vec<Edit>,
+    undone : Vec<Edit>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            done : Vec::new(),
+            undone : Vec::new(),
+        }
+    }
+    /// Applies the edit to the character and records it, unless it fails.
+    /// Applying a new edit discards all edits that could have been redone.
+    pub fn apply(&mut self, character : &mut Character, edit : Edit) -> Result<(), Error> {
+        character.apply(&edit)?;
+        self.done.push(edit);
+        self.undone.clear();
+        Ok(())
+    }
+    /// Reverts the last edit by rebuilding the character from the remaining log.
+    /// Returns false if there was nothing to undo. If rebuilding fails, the edit stays in the log.
+    pub fn undo(&mut self, character : &mut Character) -> Result<bool, Error> {
+        match self.done.pop() {
+            Some(edit) => {
+                if let Err(error) = self.rebuild(character) {
+                    self.done.push(edit);
+                    return Err(error);
+                }
+                self.undone.push(edit);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+    /// Applies the last undone edit again. Returns false if there was nothing to redo.
+    /// If the edit fails, it can still be redone later.
+    pub fn redo(&mut self, character : &mut Character) -> Result<bool, Error> {
+        match self.undone.pop() {
+            Some(edit) => {
+                if let Err(error) = character.apply(&edit) {
+                    self.undone.push(edit);
+                    return Err(error);
+                }
+                self.done.push(edit);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+    /// Creates a new character from the edits in the log, drawing on the data in the store passed to it
+    pub fn replay<'d>(&self, data : &'d Datastore) -> Result<Character<'d>, Error> {
+        let mut character = Character::new(data);
+        for edit in &self.done {
+            character.apply(edit)?;
+        }
+        Ok(character)
+    }
+    /// Returns the edits that make up the current state of the character, oldest first
+    pub fn edits(&self) -> &[Edit] {
+        &self.done
+    }
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+    /// Replaces the character by a replay of the log. What happened during play is not an edit
+    /// and is kept as it was, which is exactly: expended spell slots, uses of innate spells, conditions,
+    /// exhaustion, lost hit points and temporary hit points. Everything else a character holds has to be changed
+    /// through edits, or it is reset by undoing. This is the only place that lists the play state.
+    fn rebuild(&self, character : &mut Character) -> Result<(), Error> {
+        let mut rebuilt = self.replay(character.data)?;
+        std::mem::swap(&mut rebuilt.used_spell_slots, &mut character.used_spell_slots);
+        std::mem::swap(&mut rebuilt.used_short_rest_spell_slots, &mut character.used_short_rest_spell_slots);
+        std::mem::swap(&mut rebuilt.conditions, &mut character.conditions);
+        std::mem::swap(&mut rebuilt.exhaustion, &mut character.exhaustion);
+        std::mem::swap(&mut rebuilt.used_innate_spells, &mut character.used_innate_spells);
+        std::mem::swap(&mut rebuilt.hit_points_lost, &mut character.hit_points_lost);
+        std::mem::swap(&mut rebuilt.temporary_hit_points, &mut character.temporary_hit_points);
+        *character = rebuilt;
+        Ok(())
+    }
+}
+
+impl<'d> Character<'d> {
+    /// Applies the edit to the character, looking up everything it refers to in the datastore
+    pub fn apply(&mut self, edit : &Edit) -> Result<(), Error> {
+        let data = self.data;
+        match edit {
+            Edit::SetName(name) => { self.name = name.clone(); },
+            Edit::SetAbility(ability, score) => self.set_ability(ability, *score),
+            Edit::SetLevel(level) => self.set_level(*level)?,
+            Edit::SetRace(race) => match data.get_race(race) {
+                Some(race) => self.set_race(race)?,
+                None => { return Err(Error::missing(DataKind::Race, race)); },
+            },
+            Edit::SetSubrace(subrace) => match self.race_data()?.get_subrace(subrace) {
+                Some(subrace) => self.set_subrace(subrace)?,
+                None => { return Err(Error::missing(DataKind::Subrace, subrace)); },
+            },
+            Edit::SetClass(class) => match data.get_class(class) {
+                Some(class) => self.set_class(class)?,
+                None => { return Err(Error::missing(DataKind::Class, class)); },
+            },
+            Edit::LearnLanguage(language) => self.learn_language(language.clone()),
+            Edit::UnlearnLanguage(language) => self.unlearn_language(language),
+            Edit::SetSkillLevel(skill, level) => self.set_skill_level(skill, *level),
+            Edit::SetToolLevel(tool, level) => match data.get_tool(tool) {
+                Some(tool) => self.set_tool_level(tool, *level),
+                None => { return Err(Error::missing(DataKind::Tool, tool)); },
+            },
+            Edit::ChooseTool(tool) => match data.get_tool(tool) {
+                Some(tool) => self.choose_tool(tool)?,
+                None => { return Err(Error::missing(DataKind::Tool, tool)); },
+            },
+            Edit::AddCombatProficiency(prof) => self.add_combat_proficiency(prof.clone()),
+            Edit::LearnFeat(feat) => match data.get_feat(feat) {
+                Some(feat) => self.learn_feat(feat)?,
+                None => { return Err(Error::missing(DataKind::Feat, feat)); },
+            },
+            Edit::UnlearnFeat(feat) => match data.get_feat(feat) {
+                Some(feat) => self.unlearn_feat(feat),
+                None => { return Err(Error::missing(DataKind::Feat, feat)); },
+            },
+            Edit::LearnSpell(spell, ability) => match data.get_spell(spell) {
+                Some(spell) => self.learn_spell(spell, *ability),
+                None => { return Err(Error::missing(DataKind::Spell, spell)); },
+            },
+            Edit::UnlearnSpell(spell, ability) => match data.get_spell(spell) {
+                Some(spell) => self.unlearn_spell(spell, *ability),
+                None => { return Err(Error::missing(DataKind::Spell, spell)); },
+            },
+            Edit::LevelUp(level_up) => self.apply_level_up(level_up.clone())?,
+            Edit::LevelDown => self.level_down()?,
+            Edit::ReviseImprovement(level, improvement) => self.revise_improvement(*level, improvement.clone())?,
+            Edit::SetAdvancement(advancement) => self.set_advancement(*advancement),
+            Edit::AwardXp(experience) => { self.award_xp(*experience)?; },
+            Edit::AwardMilestone => { self.award_milestone()?; },
+            Edit::AddItem(kind, name, quantity) => { self.add_item(*kind, name, *quantity)?; },
+            Edit::RemoveItem(id, quantity) => self.remove_item(*id, *quantity)?,
+            Edit::MoveItem(id, container) => self.move_item(*id, *container)?,
+            Edit::Equip(id) => self.equip(*id)?,
+            Edit::Unequip(id) => self.unequip(*id)?,
+            Edit::Attune(id) => self.attune(*id)?,
+            Edit::EndAttunement(id) => self.end_attunement(*id)?,
+            Edit::GainCurrency(currency) => self.gain_currency(currency)?,
+            Edit::SpendCurrency(currency) => self.spend_currency(currency)?,
+            Edit::Buy(kind, name, quantity, price) => { self.pay_for_items(*kind, name, *quantity, price)?; },
+            Edit::Sell(id, quantity, payment) => self.get_paid_for_items(*id, *quantity, payment)?,
+            Edit::SetEncumbranceRules(rules) => self.set_encumbrance_rules(*rules),
+            Edit::SetBackground(background) => match data.get_background(background) {
+                Some(background) => self.set_background(background),
+                None => { return Err(Error::missing(DataKind::Background, background)); },
+            },
+            Edit::TakeStartingEquipment(class_choice, background_choice) => self.take_starting_equipment(class_choice, background_choice.as_ref())?,
+        };
+        Ok(())
+    }
+}
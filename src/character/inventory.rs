@@ -0,0 +1,404 @@
+use super::Character;
+use crate::datastore::{ Weight, POUND, Weapon, Armor, ArmorCategory, MagicItemBase, Attunement, AttunementRequirement };
+use crate::error::{ Error, DataKind };
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+/// The number of items a character can be attuned to at the same time
+pub const ATTUNEMENT_SLOTS : usize = 3;
+/// Fifty coins of any kind weigh a pound
+const COIN_WEIGHT : Weight = POUND / 50;
+/// The value of each kind of coin in copper pieces
+const CP_VALUE : u32 = 1;
+const SP_VALUE : u32 = 10;
+const EP_VALUE : u32 = 50;
+const GP_VALUE : u32 = 100;
+const PP_VALUE : u32 = 1000;
+
+/// Which part of the datastore an inventory entry refers to
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ItemKind { Weapon, Armor, Item, MagicItem }
+
+/// Identifies an entry of an inventory for as long as the entry exists
+pub type EntryId = usize;
+
+/// A stack of identical items in an inventory
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct InventoryEntry {
+    kind : ItemKind,
+    name : String,
+    quantity : u32,
+    container : Option<EntryId>,
+    equipped : bool,
+    attuned : bool,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct Currency {
+    pub cp : u32,
+    pub sp : u32,
+    pub ep : u32,
+    pub gp : u32,
+    pub pp : u32,
+}
+
+pub struct Inventory {
+    entries : BTreeMap<EntryId, InventoryEntry>,
+    next_id : EntryId,
+    currency : Currency,
+}
+
+impl ItemKind {
+    fn data_kind(&self) -> DataKind {
+        match self {
+            ItemKind::Weapon => DataKind::Weapon,
+            ItemKind::Armor => DataKind::Armor,
+            ItemKind::Item => DataKind::Item,
+            ItemKind::MagicItem => DataKind::MagicItem,
+        }
+    }
+}
+
+impl InventoryEntry {
+    pub fn kind(&self) -> ItemKind { self.kind }
+    pub fn name(&self) -> &str { &self.name }
+    pub fn quantity(&self) -> u32 { self.quantity }
+    /// Returns the entry this entry is stored in, or None if it is carried directly
+    pub fn container(&self) -> Option<EntryId> { self.container }
+    pub fn is_equipped(&self) -> bool { self.equipped }
+    pub fn is_attuned(&self) -> bool { self.attuned }
+}
+
+impl Currency {
+    /// Returns the number of coins regardless of their value
+    pub fn coins(&self) -> u64 {
+        [self.cp, self.sp, self.ep, self.gp, self.pp].iter().map( |coins| u64::from(*coins) ).sum()
+    }
+    /// Converts an amount of copper pieces into as few gold, silver and copper pieces as possible
+    pub fn from_copper(copper : u32) -> Self {
+        Currency {
+            gp : copper / GP_VALUE,
+            sp : copper % GP_VALUE / SP_VALUE,
+            cp : copper % SP_VALUE,
+            ..Currency::default()
+        }
+    }
+    /// Returns the total value of the coins in copper pieces
+    pub fn value(&self) -> u64 {
+        u64::from(self.cp) * u64::from(CP_VALUE) + u64::from(self.sp) * u64::from(SP_VALUE) + u64::from(self.ep) * u64::from(EP_VALUE)
+            + u64::from(self.gp) * u64::from(GP_VALUE) + u64::from(self.pp) * u64::from(PP_VALUE)
+    }
+    /// Adds the coins of the other amount, failing without changes if there would be more coins of a kind than can be counted
+    pub fn add(&mut self, other : &Currency) -> Result<(), Error> {
+        let sum = |ours : u32, theirs : u32| ours.checked_add(theirs).ok_or_else( || Error::Validation("There are too many coins.".to_owned()) );
+        *self = Currency {
+            cp : sum(self.cp, other.cp)?,
+            sp : sum(self.sp, other.sp)?,
+            ep : sum(self.ep, other.ep)?,
+            gp : sum(self.gp, other.gp)?,
+            pp : sum(self.pp, other.pp)?,
+        };
+        Ok(())
+    }
+    /// Removes coins worth the amount, starting with the smallest coins. If no combination of coins
+    /// pays the exact amount, one larger coin is broken and the change is added back.
+    /// Nothing is removed if paying fails.
+    pub fn pay(&mut self, price : &Currency) -> Result<(), Error> {
+        let mut remaining = price.value();
+        if remaining > self.value() {
+            return Err(Error::InsufficientFunds);
+        }
+        let mut wallet = *self;
+        for (coins, value) in wallet.by_value() {
+            let paid = std::cmp::min(u64::from(*coins), remaining / u64::from(value));
+            // paid is at most the number of coins, so it fits
+            *coins -= paid as u32;
+            remaining -= paid * u64::from(value);
+        }
+        if remaining > 0 {
+            // Every coin left is worth more than what remains to be paid
+            if let Some((coins, value)) = wallet.by_value().into_iter().find( |(coins, _)| **coins > 0 ) {
+                *coins -= 1;
+                // remaining is less than the value of a single coin here
+                wallet.add(&Currency::from_copper(value - remaining as u32))?;
+            }
+        }
+        *self = wallet;
+        Ok(())
+    }
+    /// Returns the coins of each kind with their value, from the least to the most valuable
+    fn by_value(&mut self) -> Vec<(&mut u32, u32)> {
+        vec![(&mut self.cp, CP_VALUE), (&mut self.sp, SP_VALUE), (&mut self.ep, EP_VALUE), (&mut self.gp, GP_VALUE), (&mut self.pp, PP_VALUE)]
+    }
+}
+
+impl Inventory {
+    pub(super) fn new() -> Self {
+        Inventory {
+            entries : BTreeMap::new(),
+            next_id : 0,
+            currency : Currency::default(),
+        }
+    }
+    pub fn entry(&self, id : EntryId) -> Option<&InventoryEntry> {
+        self.entries.get(&id)
+    }
+    /// Returns all entries of the inventory, including those stored in containers
+    pub fn entries(&self) -> Vec<(EntryId, &InventoryEntry)> {
+        self.entries.iter().map( |(id, entry)| (*id, entry) ).collect()
+    }
+    /// Returns the entries stored directly in the container, or carried directly if the container is None
+    pub fn contents(&self, container : Option<EntryId>) -> Vec<(EntryId, &InventoryEntry)> {
+        self.entries.iter()
+            .filter( |(_, entry)| entry.container == container )
+            .map( |(id, entry)| (*id, entry) )
+            .collect()
+    }
+    pub fn equipped(&self) -> Vec<(EntryId, &InventoryEntry)> {
+        self.entries.iter()
+            .filter( |(_, entry)| entry.equipped )
+            .map( |(id, entry)| (*id, entry) )
+            .collect()
+    }
+    pub fn attuned(&self) -> Vec<(EntryId, &InventoryEntry)> {
+        self.entries.iter()
+            .filter( |(_, entry)| entry.attuned )
+            .map( |(id, entry)| (*id, entry) )
+            .collect()
+    }
+    pub fn currency(&self) -> &Currency {
+        &self.currency
+    }
+    pub(super) fn get(&self, id : EntryId) -> Result<&InventoryEntry, Error> {
+        self.entries.get(&id).ok_or(Error::UnknownInventoryEntry(id))
+    }
+    fn get_mut(&mut self, id : EntryId) -> Result<&mut InventoryEntry, Error> {
+        self.entries.get_mut(&id).ok_or(Error::UnknownInventoryEntry(id))
+    }
+}
+
+impl<'d> Character<'d> {
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+    /// Adds items from the datastore to the inventory. They are stacked onto an entry of
+    /// the same items that is carried directly and unequipped, if there is one.
+    pub fn add_item(&mut self, kind : ItemKind, name : &str, quantity : u32) -> Result<EntryId, Error> {
+        self.item_weight(kind, name)?;
+        let stack = self.inventory.entries.iter_mut().find( |(_, entry)|
+            entry.kind == kind && entry.name == name && entry.container.is_none() && !entry.equipped && !entry.attuned
+        );
+        if let Some((id, entry)) = stack {
+            entry.quantity = entry.quantity.checked_add(quantity)
+                .ok_or_else( || Error::Validation(format!("There cannot be more of \"{}\" in one entry.", name)) )?;
+            return Ok(*id);
+        }
+        let id = self.inventory.next_id;
+        self.inventory.next_id += 1;
+        self.inventory.entries.insert(id, InventoryEntry {
+            kind,
+            name : name.to_owned(),
+            quantity,
+            container : None,
+            equipped : false,
+            attuned : false,
+        });
+        Ok(id)
+    }
+    /// Removes some of the items of an entry. If none are left, the entry is removed
+    /// and everything stored in it is moved to where it was stored.
+    pub fn remove_item(&mut self, id : EntryId, quantity : u32) -> Result<(), Error> {
+        let entry = self.inventory.get_mut(id)?;
+        if quantity > entry.quantity {
+            return Err(Error::Validation(format!("Inventory has only {} of \"{}\".", entry.quantity, entry.name)));
+        }
+        entry.quantity -= quantity;
+        if entry.quantity == 0 {
+            let container = entry.container;
+            self.inventory.entries.remove(&id);
+            for entry in self.inventory.entries.values_mut().filter( |entry| entry.container == Some(id) ) {
+                entry.container = container;
+            }
+        }
+        Ok(())
+    }
+    /// Stores an entry in a container, or carries it directly if the container is None
+    pub fn move_item(&mut self, id : EntryId, container : Option<EntryId>) -> Result<(), Error> {
+        if self.inventory.get(id)?.equipped {
+            return Err(Error::Validation("Equipped items cannot be stored in containers.".to_owned()));
+        }
+        if let Some(container_id) = container {
+            let container_entry = self.inventory.get(container_id)?;
+            let capacity = match self.data.get_item(&container_entry.name) {
+                Some(item) if container_entry.kind == ItemKind::Item => item.capacity,
+                _ => None,
+            };
+            let capacity = match capacity {
+                Some(capacity) => capacity,
+                None => { return Err(Error::Validation(format!("\"{}\" cannot hold other items.", container_entry.name))); },
+            };
+            let mut ancestor = Some(container_id);
+            while let Some(ancestor_id) = ancestor {
+                if ancestor_id == id {
+                    return Err(Error::Validation("Containers cannot be stored inside themselves.".to_owned()));
+                }
+                ancestor = self.inventory.get(ancestor_id)?.container;
+            }
+            let mut stored = self.contents_weight(container_id)?;
+            if self.inventory.get(id)?.container == container {
+                stored = stored.saturating_sub(self.stack_weight(id)?);
+            }
+            if stored.saturating_add(self.stack_weight(id)?) > capacity {
+                return Err(Error::Validation(format!("\"{}\" cannot hold that much weight.", container_entry.name)));
+            }
+        }
+        self.inventory.get_mut(id)?.container = container;
+        Ok(())
+    }
+    /// Equips an entry that is carried directly. Only one suit of armor and one shield can be worn at a time.
+    pub fn equip(&mut self, id : EntryId) -> Result<(), Error> {
+        let entry = self.inventory.get(id)?;
+        if entry.container.is_some() {
+            return Err(Error::Validation("Items stored in containers cannot be equipped.".to_owned()));
+        }
+        if let Some(armor) = self.entry_armor(entry)? {
+            let is_shield = armor.category == ArmorCategory::Shield;
+            for (other_id, other) in self.inventory.equipped() {
+                if other_id == id {
+                    continue;
+                }
+                if let Some(other_armor) = self.entry_armor(other)? {
+                    if ( other_armor.category == ArmorCategory::Shield ) == is_shield {
+                        return Err(Error::AlreadyEquipped(other.name.clone()));
+                    }
+                }
+            }
+        }
+        self.inventory.get_mut(id)?.equipped = true;
+        Ok(())
+    }
+    pub fn unequip(&mut self, id : EntryId) -> Result<(), Error> {
+        self.inventory.get_mut(id)?.equipped = false;
+        Ok(())
+    }
+    /// Attunes the character to an entry, which takes up one of their limited attunement slots.
+    /// Only magic items that require attunement can be attuned, and some restrict it to characters of certain classes or races.
+    pub fn attune(&mut self, id : EntryId) -> Result<(), Error> {
+        let entry = self.inventory.get(id)?;
+        if entry.attuned {
+            return Ok(());
+        }
+        if entry.kind != ItemKind::MagicItem {
+            return Err(Error::Validation(format!("\"{}\" is not a magic item.", entry.name)));
+        }
+        let item = self.data.get_magic_item(&entry.name).ok_or_else( || Error::missing(DataKind::MagicItem, &entry.name) )?;
+        match &item.attunement {
+            Attunement::NotRequired => {
+                return Err(Error::Validation(format!("\"{}\" does not require attunement.", item.name)));
+            },
+            Attunement::Required(requirements) => if !requirements.is_empty() && !requirements.iter().any( |requirement| self.meets_attunement_requirement(requirement) ) {
+                return Err(Error::Validation(format!("Character cannot attune to \"{}\".", item.name)));
+            },
+        }
+        if self.inventory.attuned().len() >= ATTUNEMENT_SLOTS {
+            return Err(Error::AttunementLimit);
+        }
+        self.inventory.get_mut(id)?.attuned = true;
+        Ok(())
+    }
+    pub fn end_attunement(&mut self, id : EntryId) -> Result<(), Error> {
+        self.inventory.get_mut(id)?.attuned = false;
+        Ok(())
+    }
+    pub fn gain_currency(&mut self, currency : &Currency) -> Result<(), Error> {
+        self.inventory.currency.add(currency)
+    }
+    /// Pays the amount from the coins of the character, getting change if needed
+    pub fn spend_currency(&mut self, currency : &Currency) -> Result<(), Error> {
+        self.inventory.currency.pay(currency)
+    }
+    /// Returns the weight of everything the character carries, including their coins.
+    /// Weights too large to count are capped at the largest weight there is.
+    pub fn carried_weight(&self) -> Result<Weight, Error> {
+        let mut weight = Weight::try_from(self.inventory.currency.coins()).unwrap_or(Weight::MAX).saturating_mul(COIN_WEIGHT);
+        for entry in self.inventory.entries.values() {
+            weight = weight.saturating_add(self.item_weight(entry.kind, &entry.name)?.saturating_mul(entry.quantity));
+        }
+        Ok(weight)
+    }
+    /// Looks up the weight of a single item in the datastore. Magic items weigh as much as what they are based on.
+    pub(super) fn item_weight(&self, kind : ItemKind, name : &str) -> Result<Weight, Error> {
+        let weight = match kind {
+            ItemKind::Weapon => self.data.get_weapon(name).map( |weapon| weapon.weight ),
+            ItemKind::Armor => self.data.get_armor(name).map( |armor| armor.weight ),
+            ItemKind::Item => self.data.get_item(name).map( |item| item.weight ),
+            ItemKind::MagicItem => match self.data.get_magic_item(name).map( |item| &item.base ) {
+                Some(MagicItemBase::Weapon(weapon)) => Some(self.item_weight(ItemKind::Weapon, weapon)?),
+                Some(MagicItemBase::Armor(armor)) => Some(self.item_weight(ItemKind::Armor, armor)?),
+                Some(MagicItemBase::Wondrous(weight)) => Some(*weight),
+                None => None,
+            },
+        };
+        weight.ok_or_else( || Error::missing(kind.data_kind(), name) )
+    }
+    /// Returns the weight of an entry including everything stored in it
+    fn stack_weight(&self, id : EntryId) -> Result<Weight, Error> {
+        let entry = self.inventory.get(id)?;
+        Ok(self.item_weight(entry.kind, &entry.name)?.saturating_mul(entry.quantity).saturating_add(self.contents_weight(id)?))
+    }
+    fn contents_weight(&self, container : EntryId) -> Result<Weight, Error> {
+        let mut weight : Weight = 0;
+        for (id, _) in self.inventory.contents(Some(container)) {
+            weight = weight.saturating_add(self.stack_weight(id)?);
+        }
+        Ok(weight)
+    }
+    /// Looks up the price of a single item in the datastore. Magic items cannot be bought or sold.
+    pub(super) fn item_price(&self, kind : ItemKind, name : &str) -> Result<Currency, Error> {
+        let price = match kind {
+            ItemKind::Weapon => self.data.get_weapon(name).map( |weapon| weapon.price ),
+            ItemKind::Armor => self.data.get_armor(name).map( |armor| armor.price ),
+            ItemKind::Item => self.data.get_item(name).map( |item| item.price ),
+            ItemKind::MagicItem => { return Err(Error::Validation(format!("\"{}\" has no price.", name))); },
+        };
+        price.ok_or_else( || Error::missing(kind.data_kind(), name) )
+    }
+    /// Returns the armor an entry is, or is based on if it is a magic item
+    pub(super) fn entry_armor(&self, entry : &InventoryEntry) -> Result<Option<&'d Armor>, Error> {
+        let armor = match entry.kind {
+            ItemKind::Armor => &entry.name,
+            ItemKind::MagicItem => match self.data.get_magic_item(&entry.name).map( |item| &item.base ) {
+                Some(MagicItemBase::Armor(armor)) => armor,
+                Some(_) => { return Ok(None); },
+                None => { return Err(Error::missing(DataKind::MagicItem, &entry.name)); },
+            },
+            _ => { return Ok(None); },
+        };
+        match self.data.get_armor(armor) {
+            Some(armor) => Ok(Some(armor)),
+            None => Err(Error::missing(DataKind::Armor, armor)),
+        }
+    }
+    /// Returns the weapon an entry is, or is based on if it is a magic item
+    pub(super) fn entry_weapon(&self, entry : &InventoryEntry) -> Result<Option<&'d Weapon>, Error> {
+        let weapon = match entry.kind {
+            ItemKind::Weapon => &entry.name,
+            ItemKind::MagicItem => match self.data.get_magic_item(&entry.name).map( |item| &item.base ) {
+                Some(MagicItemBase::Weapon(weapon)) => weapon,
+                Some(_) => { return Ok(None); },
+                None => { return Err(Error::missing(DataKind::MagicItem, &entry.name)); },
+            },
+            _ => { return Ok(None); },
+        };
+        match self.data.get_weapon(weapon) {
+            Some(weapon) => Ok(Some(weapon)),
+            None => Err(Error::missing(DataKind::Weapon, weapon)),
+        }
+    }
+    fn meets_attunement_requirement(&self, requirement : &AttunementRequirement) -> bool {
+        match requirement {
+            AttunementRequirement::Class(class) => self.class_level(class) > 0,
+            AttunementRequirement::Race(race) => self.race == *race || self.subrace == *race,
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use super::{ Character, ClassLevel, Ability, AbilityScore, Level, Die };
+use crate::datastore::{ Datastore, Class, ClassFeature, SpellLevel, SpellCaster, ConditionEffect };
+use crate::error::{ Error, DataKind };
+
+/// How the hit points gained on a new level are determined
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum HitPointChoice { Average, Rolled(u8) }
+
+/// Something that has to be decided before a new level can be applied to a character
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Decision {
+    /// Roll the hit die or take its average
+    HitPoints(Die),
+    /// Choose one of the subclasses of the class
+    Subclass(Vec<String>),
+    /// Learn new spells, none of which may be above the maximum level
+    Spells { count : u8, max_level : SpellLevel },
+    /// Choose one of the options of a newly gained class feature
+    Feature { name : String, options : Vec<String> },
+    /// Increase ability scores or learn a feat instead
+    AbilityScoreImprovement,
+}
+
+/// The answer to one of the decisions of a level up
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Choice {
+    HitPoints(HitPointChoice),
+    Subclass(String),
+    Spells(Vec<String>),
+    Feature(String),
+    AbilityScoreImprovement(Improvement),
+}
+
+/// What a character gains from an ability score improvement
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Improvement {
+    /// Increases two abilities by one each, or one ability by two if it is named twice.
+    /// No ability score can be increased above 20 this way.
+    Increase(Ability, Ability),
+    Feat(String),
+}
+
+/// A pending level up of a character in one class. Nothing happens to the character
+/// until all decisions are resolved and the level up is applied to it.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct LevelUp {
+    class : String,
+    class_level : Level,
+    features : Vec<ClassFeature>,
+    decisions : Vec<Decision>,
+    choices : Vec<Option<Choice>>,
+    /// How many of the features and decisions come before those of a subclass chosen in this level up
+    class_features : usize,
+    class_decisions : usize,
+}
+
+/// What was decided when a level up was applied, so it can be reverted again
+pub(super) struct LevelUpRecord {
+    class : String,
+    level : Level,
+    hit_points : u8,
+    subclass : Option<String>,
+    spells : Vec<String>,
+    spell_ability : Option<Ability>,
+    feature_choices : Vec<(String, String)>,
+    improvement : Option<Improvement>,
+}
+
+const ABILITY_SCORE_MAXIMUM : AbilityScore = 20;
+
+impl LevelUp {
+    pub fn class(&self) -> &str { &self.class }
+    /// Returns the level the character will have in the class after the level up
+    pub fn class_level(&self) -> Level { self.class_level }
+    /// Returns all class features gained with the new level, including those of a subclass chosen in this level up
+    pub fn features(&self) -> &[ClassFeature] { &self.features }
+    pub fn decisions(&self) -> &[Decision] { &self.decisions }
+    /// Answers the decision with the given index, replacing any earlier answer to it.
+    /// Choosing a subclass adds its features for the new level and the decisions they need.
+    pub fn resolve(&mut self, decision : usize, choice : Choice, data : &Datastore) -> Result<(), Error> {
+        let valid = match (self.decisions.get(decision), &choice) {
+            (Some(Decision::HitPoints(die)), Choice::HitPoints(HitPointChoice::Rolled(roll))) => *roll >= 1 && *roll <= die.sides(),
+            (Some(Decision::HitPoints(_)), Choice::HitPoints(HitPointChoice::Average)) => true,
+            (Some(Decision::Subclass(options)), Choice::Subclass(subclass)) => options.contains(subclass),
+            (Some(Decision::Feature { options, .. }), Choice::Feature(option)) => options.contains(option),
+            (Some(Decision::AbilityScoreImprovement), Choice::AbilityScoreImprovement(Improvement::Increase(_, _))) => true,
+            (Some(Decision::AbilityScoreImprovement), Choice::AbilityScoreImprovement(Improvement::Feat(feat))) => {
+                if data.get_feat(feat).is_none() {
+                    return Err(Error::missing(DataKind::Feat, feat));
+                }
+                true
+            },
+            (Some(Decision::Spells { count, max_level }), Choice::Spells(spells)) => {
+                for spell in spells {
+                    match data.get_spell(spell) {
+                        Some(spell) => if spell.level > *max_level {
+                            return Err(Error::Validation(format!("The spell \"{}\" is above level {:?}.", spell.name, max_level)));
+                        },
+                        None => { return Err(Error::missing(DataKind::Spell, spell)); },
+                    }
+                }
+                let mut distinct = spells.clone();
+                distinct.sort();
+                distinct.dedup();
+                distinct.len() == spells.len() && spells.len() == *count as usize
+            },
+            (None, _) => { return Err(Error::Validation(format!("The level up has no decision {}.", decision))); },
+            _ => false,
+        };
+        if !valid {
+            return Err(Error::Validation(format!("{:?} does not answer {:?}.", choice, self.decisions[decision])));
+        }
+        if let Choice::Subclass(subclass) = &choice {
+            self.choose_subclass(subclass, data)?;
+        }
+        self.choices[decision] = Some(choice);
+        Ok(())
+    }
+    /// Returns true if all decisions have been made
+    pub fn is_resolved(&self) -> bool {
+        self.choices.iter().all( |choice| choice.is_some() )
+    }
+    /// Replaces the features and decisions of any previously chosen subclass by those of the new one
+    fn choose_subclass(&mut self, subclass : &str, data : &Datastore) -> Result<(), Error> {
+        let class = data.get_class(&self.class).ok_or_else( || Error::missing(DataKind::Class, &self.class) )?;
+        let subclass = class.get_subclass(subclass)
+            .ok_or_else( || Error::Validation(format!("\"{}\" is not a subclass of \"{}\".", subclass, class.name)) )?;
+        self.features.truncate(self.class_features);
+        self.decisions.truncate(self.class_decisions);
+        self.choices.truncate(self.class_decisions);
+        for feature in subclass.features_at(self.class_level) {
+            if !feature.options.is_empty() {
+                self.decisions.push(Decision::Feature { name : feature.name.clone(), options : feature.options.clone() });
+                self.choices.push(None);
+            }
+            self.features.push(feature.clone());
+        }
+        Ok(())
+    }
+}
+
+impl<'d> Character<'d> {
+    /// Starts a level up of the character in the specified class, which may be a new class for them.
+    /// The returned level up lists all decisions that have to be made for the new level.
+    pub fn level_up(&self, class : &Class) -> Result<LevelUp, Error> {
+        let class_level = self.class_level(&class.name) + 1;
+        if !self.classes.is_empty() && self.level >= 20 {
+            return Err(Error::InvalidLevel(self.level + 1));
+        }
+        let mut decisions = Vec::new();
+        if !self.classes.is_empty() {
+            decisions.push(Decision::HitPoints(class.hit_die));
+        }
+        if class_level == class.subclass_level && !class.subclasses.is_empty() {
+            let mut options : Vec<String> = class.subclasses.keys().cloned().collect();
+            options.sort();
+            decisions.push(Decision::Subclass(options));
+        }
+        let spell_count = class.spells_learned_at(class_level);
+        if spell_count > 0 && class.spellcasting_ability.is_some() {
+            decisions.push(Decision::Spells { count : spell_count, max_level : self.highest_spell_level(class, class_level)? });
+        }
+        if class.asi_levels.contains(&class_level) {
+            decisions.push(Decision::AbilityScoreImprovement);
+        }
+        let mut features : Vec<ClassFeature> = class.features_at(class_level).into_iter().cloned().collect();
+        if let Some(subclass) = self.subclass(&class.name).and_then( |subclass| class.get_subclass(subclass) ) {
+            features.extend(subclass.features_at(class_level).into_iter().cloned());
+        }
+        for feature in features.iter().filter( |feature| !feature.options.is_empty() ) {
+            decisions.push(Decision::Feature { name : feature.name.clone(), options : feature.options.clone() });
+        }
+        Ok(LevelUp {
+            class : class.name.clone(),
+            class_level,
+            class_features : features.len(),
+            class_decisions : decisions.len(),
+            features,
+            choices : vec![None; decisions.len()],
+            decisions,
+        })
+    }
+    /// Applies a fully resolved level up to the character. Nothing is changed if it cannot be applied.
+    pub fn apply_level_up(&mut self, level_up : LevelUp) -> Result<(), Error> {
+        if !level_up.is_resolved() {
+            return Err(Error::Validation("Not all decisions of the level up have been made.".to_owned()));
+        }
+        if level_up.class_level != self.class_level(&level_up.class) + 1 {
+            return Err(Error::Validation("The level up does not fit the current level of the character.".to_owned()));
+        }
+        let class = match self.data.get_class(&level_up.class) {
+            Some(class) => class,
+            None => { return Err(Error::missing(DataKind::Class, &level_up.class)); },
+        };
+        if self.classes.is_empty() && self.level > 1 {
+            return Err(Error::Validation("The first class has to be gained at the first level.".to_owned()));
+        }
+        let mut record = LevelUpRecord {
+            class : class.name.clone(),
+            level : if self.classes.is_empty() { 1 } else { self.level + 1 },
+            hit_points : class.hit_die.sides(),
+            subclass : None,
+            spells : Vec::new(),
+            spell_ability : class.spellcasting_ability,
+            feature_choices : Vec::new(),
+            improvement : None,
+        };
+        for (decision, choice) in level_up.decisions.iter().zip(level_up.choices.into_iter().flatten()) {
+            match choice {
+                Choice::HitPoints(HitPointChoice::Average) => { record.hit_points = class.hit_die.average(); },
+                Choice::HitPoints(HitPointChoice::Rolled(roll)) => { record.hit_points = roll; },
+                Choice::Subclass(subclass) => { record.subclass = Some(subclass); },
+                Choice::Spells(spells) => { record.spells = spells; },
+                Choice::Feature(option) => if let Decision::Feature { name, .. } = decision {
+                    record.feature_choices.push((name.clone(), option));
+                },
+                Choice::AbilityScoreImprovement(improvement) => {
+                    self.check_improvement(&improvement)?;
+                    record.improvement = Some(improvement);
+                },
+            }
+        }
+        if self.classes.is_empty() {
+            self.level = 1;
+        } else {
+            self.level += 1;
+        }
+        self.catch_up_milestones();
+        match self.classes.iter_mut().find( |class_level| class_level.class == class.name ) {
+            Some(class_level) => { class_level.level += 1; },
+            None => self.classes.push(ClassLevel { class : class.name.clone(), level : 1, subclass : None }),
+        }
+        if let Some(subclass) = &record.subclass {
+            for class_level in self.classes.iter_mut().filter( |class_level| class_level.class == class.name ) {
+                class_level.subclass = Some(subclass.clone());
+            }
+        }
+        let data = self.data;
+        if let Some(ability) = record.spell_ability {
+            for spell in record.spells.iter().filter_map( |spell| data.get_spell(spell) ) {
+                self.learn_spell(spell, ability);
+            }
+        }
+        if let Some(improvement) = &record.improvement {
+            self.apply_improvement(improvement)?;
+        }
+        self.level_ups.push(record);
+        Ok(())
+    }
+    /// Replaces the ability score improvement chosen on reaching the specified character level.
+    /// If the new improvement cannot be applied, the old one is kept.
+    pub fn revise_improvement(&mut self, level : Level, improvement : Improvement) -> Result<(), Error> {
+        let index = match self.level_ups.iter().position( |record| record.level == level && record.improvement.is_some() ) {
+            Some(index) => index,
+            None => { return Err(Error::Validation(format!("Character has no ability score improvement at level {}.", level))); },
+        };
+        let old_improvement = self.level_ups[index].improvement.take().unwrap();
+        self.revert_improvement(&old_improvement);
+        let result = self.check_improvement(&improvement);
+        match result {
+            Ok(()) => {
+                self.apply_improvement(&improvement)?;
+                self.level_ups[index].improvement = Some(improvement);
+            },
+            Err(_) => {
+                self.apply_improvement(&old_improvement)?;
+                self.level_ups[index].improvement = Some(old_improvement);
+            },
+        }
+        result
+    }
+    /// Returns the ability score improvements of the character together with the character level they were gained at
+    pub fn improvements(&self) -> Vec<(Level, &Improvement)> {
+        self.level_ups.iter()
+            .filter_map( |record| record.improvement.as_ref().map( |improvement| (record.level, improvement) ) )
+            .collect()
+    }
+    fn check_improvement(&self, improvement : &Improvement) -> Result<(), Error> {
+        match improvement {
+            Improvement::Increase(first, second) => {
+                let exceeds_maximum = if first == second {
+                    *self.abilities.get(first) + 2 > ABILITY_SCORE_MAXIMUM
+                } else {
+                    *self.abilities.get(first) + 1 > ABILITY_SCORE_MAXIMUM || *self.abilities.get(second) + 1 > ABILITY_SCORE_MAXIMUM
+                };
+                if exceeds_maximum {
+                    return Err(Error::Validation(format!("Ability scores cannot be improved above {}.", ABILITY_SCORE_MAXIMUM)));
+                }
+            },
+            Improvement::Feat(feat) => {
+                let feat = match self.data.get_feat(feat) {
+                    Some(feat) => feat,
+                    None => { return Err(Error::missing(DataKind::Feat, feat)); },
+                };
+                if self.feats.contains(&feat.name) {
+                    return Err(Error::Validation(format!("Character already knows the feat \"{}\".", feat.name)));
+                }
+                if !self.meets_prerequisites(feat) {
+                    return Err(Error::PrerequisitesNotMet { feat : feat.name.clone() });
+                }
+            },
+        }
+        Ok(())
+    }
+    fn apply_improvement(&mut self, improvement : &Improvement) -> Result<(), Error> {
+        match improvement {
+            Improvement::Increase(first, second) => {
+                *self.abilities.get_mut(first) += 1;
+                *self.abilities.get_mut(second) += 1;
+            },
+            Improvement::Feat(feat) => match self.data.get_feat(feat) {
+                Some(feat) => self.learn_feat(feat)?,
+                None => { return Err(Error::missing(DataKind::Feat, feat)); },
+            },
+        }
+        Ok(())
+    }
+    fn revert_improvement(&mut self, improvement : &Improvement) {
+        match improvement {
+            Improvement::Increase(first, second) => {
+                *self.abilities.get_mut(first) -= 1;
+                *self.abilities.get_mut(second) -= 1;
+            },
+            Improvement::Feat(feat) => if let Some(feat) = self.data.get_feat(feat) {
+                self.unlearn_feat(feat);
+            },
+        }
+    }
+    /// Reverts the last level up of the character, including everything that was decided for it
+    pub fn level_down(&mut self) -> Result<(), Error> {
+        let record = match self.level_ups.pop() {
+            Some(record) => record,
+            None => { return Err(Error::Validation("Character has no level up to revert.".to_owned())); },
+        };
+        if let Some(position) = self.classes.iter().position( |class_level| class_level.class == record.class ) {
+            let class_level = &mut self.classes[position];
+            class_level.level -= 1;
+            if record.subclass.is_some() {
+                class_level.subclass = None;
+            }
+            if class_level.level == 0 {
+                self.classes.remove(position);
+            }
+        }
+        self.level = std::cmp::max(1, self.level - 1);
+        let data = self.data;
+        if let Some(ability) = record.spell_ability {
+            for spell in record.spells.iter().filter_map( |spell| data.get_spell(spell) ) {
+                self.unlearn_spell(spell, ability);
+            }
+        }
+        if let Some(improvement) = &record.improvement {
+            self.revert_improvement(improvement);
+        }
+        Ok(())
+    }
+    /// Returns the highest level the character gained through a level up, or 0 if there was none
+    pub(super) fn leveled_up_to(&self) -> Level {
+        self.level_ups.last().map_or(0, |record| record.level )
+    }
+    /// Returns the maximum hit points of the character. Levels that were not gained through
+    /// a level up count with the maximum of the hit die for the first level and its average after that. Some conditions halve the maximum.
+    pub fn max_hit_points(&self) -> Result<u16, Error> {
+        let hit_die = match self.hit_die() {
+            Some(die) => die,
+            None => { return Err(Error::NotChosen(DataKind::Class)); },
+        };
+        let unrecorded = (self.level as usize).saturating_sub(self.level_ups.len());
+        let constitution = Ability::score_to_mod(self.ability(&Ability::Con)) as i16;
+        let gains = (0..unrecorded).map( |level| if level == 0 { hit_die.sides() } else { hit_die.average() } )
+            .chain(self.level_ups.iter().map( |record| record.hit_points ));
+        let maximum : u16 = gains.map( |gain| std::cmp::max(1, gain as i16 + constitution) as u16 ).sum();
+        if self.has_condition_effect(&ConditionEffect::HalveHitPointMaximum) {
+            Ok(maximum / 2)
+        } else {
+            Ok(maximum)
+        }
+    }
+    /// Returns all class and subclass features the character has gained so far
+    pub fn features(&self) -> Vec<&'d ClassFeature> {
+        let mut features = Vec::new();
+        for class_level in &self.classes {
+            if let Some(class) = self.data.get_class(&class_level.class) {
+                features.extend(class.features.iter().filter( |feature| feature.level <= class_level.level ));
+                if let Some(subclass) = class_level.subclass.as_ref().and_then( |subclass| class.get_subclass(subclass) ) {
+                    features.extend(subclass.features.iter().filter( |feature| feature.level <= class_level.level ));
+                }
+            }
+        }
+        features
+    }
+    /// Returns the option the character chose for a class feature, if they had to choose one
+    pub fn feature_choice(&self, feature : &str) -> Option<&str> {
+        self.level_ups.iter()
+            .flat_map( |record| record.feature_choices.iter() )
+            .find( |(name, _)| name == feature )
+            .map( |(_, option)| option.as_str() )
+    }
+    /// Returns the highest level of spells the class can cast at the specified class level
+    fn highest_spell_level(&self, class : &Class, class_level : Level) -> Result<SpellLevel, Error> {
+        if class.spell_caster == SpellCaster::None {
+            return Ok(SpellLevel::Cantrip);
+        }
+        let slots = match self.data.get_spell_progression(&class.spell_caster) {
+            Some(progression) => progression.slots_for_level(&class_level)?,
+            None => { return Err(Error::missing(DataKind::SpellProgression, &format!("{:?}", class.spell_caster))); },
+        };
+        Ok(slots.into_iter()
+            .filter( |(_, count)| *count > 0 )
+            .map( |(level, _)| level )
+            .max()
+            .unwrap_or(SpellLevel::Cantrip))
+    }
+}
@@ -0,0 +1,85 @@
+use super::{ Character, Ability, Modifier, ItemKind, InventoryEntry, EntryId };
+use crate::datastore::{ ArmorCategory, Attunement, MagicItem, MagicEffect };
+use crate::error::Error;
+
+/// Armor class of a character without armor, before their dexterity modifier
+const UNARMORED_ARMOR_CLASS : Modifier = 10;
+/// The highest dexterity modifier that applies to the armor class while wearing medium armor
+const MEDIUM_ARMOR_MAXIMUM_DEX : Modifier = 2;
+
+impl<'d> Character<'d> {
+    /// Returns the effects of all magic items that currently affect the character, i.e. those that
+    /// are equipped and, if they require attunement, attuned
+    pub fn magic_effects(&self) -> Vec<&'d MagicEffect> {
+        self.active_magic_items().into_iter()
+            .flat_map( |item| item.effects.iter() )
+            .collect()
+    }
+    /// Returns the names of the spells the character can cast through their magic items
+    pub fn item_spells(&self) -> Vec<&'d str> {
+        self.magic_effects().into_iter()
+            .filter_map( |effect| match effect {
+                MagicEffect::Spell(spell) => Some(spell.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+    /// Returns the armor class of the character from their equipped armor and shield, their dexterity
+    /// and their magic items
+    pub fn armor_class(&self) -> Result<Modifier, Error> {
+        let dexterity = Ability::score_to_mod(self.ability(&Ability::Dex));
+        let mut armor_class = UNARMORED_ARMOR_CLASS + dexterity;
+        let mut shield_bonus = 0;
+        for (_, entry) in self.inventory.equipped() {
+            if let Some(armor) = self.entry_armor(entry)? {
+                match armor.category {
+                    ArmorCategory::Light => { armor_class = armor.armor_class + dexterity; },
+                    ArmorCategory::Medium => { armor_class = armor.armor_class + std::cmp::min(dexterity, MEDIUM_ARMOR_MAXIMUM_DEX); },
+                    ArmorCategory::Heavy => { armor_class = armor.armor_class; },
+                    ArmorCategory::Shield => { shield_bonus = armor.armor_class; },
+                }
+            }
+        }
+        let magic_bonus : Modifier = self.magic_effects().into_iter()
+            .filter_map( |effect| match effect {
+                MagicEffect::ArmorClassBonus(bonus) => Some(*bonus),
+                _ => None,
+            })
+            .sum();
+        Ok(armor_class + shield_bonus + magic_bonus)
+    }
+    /// Returns the attack modifier of the character with a weapon in their inventory, including
+    /// the bonus of the entry if it is an active magic weapon
+    pub fn entry_attack_mod(&self, id : EntryId) -> Result<Modifier, Error> {
+        let entry = self.inventory.get(id)?;
+        match self.entry_weapon(entry)? {
+            Some(weapon) => Ok(self.get_attack_mod(weapon) + self.entry_weapon_bonus(entry)),
+            None => Err(Error::Validation(format!("\"{}\" is not a weapon.", entry.name()))),
+        }
+    }
+    /// Returns the bonus to attack and damage rolls of an entry, which only magic weapons that are equipped
+    /// and, if they require attunement, attuned have
+    pub(super) fn entry_weapon_bonus(&self, entry : &InventoryEntry) -> Modifier {
+        if entry.kind() != ItemKind::MagicItem || !entry.is_equipped() {
+            return 0;
+        }
+        match self.data.get_magic_item(entry.name()) {
+            Some(item) if entry.is_attuned() || item.attunement == Attunement::NotRequired => item.effects.iter()
+                .filter_map( |effect| match effect {
+                    MagicEffect::WeaponBonus(bonus) => Some(*bonus),
+                    _ => None,
+                })
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+    pub(super) fn active_magic_items(&self) -> Vec<&'d MagicItem> {
+        self.inventory.equipped().into_iter()
+            .filter( |(_, entry)| entry.kind() == ItemKind::MagicItem )
+            .filter_map( |(_, entry)| self.data.get_magic_item(entry.name()).map( |item| (entry, item) ) )
+            .filter( |(entry, item)| entry.is_attuned() || item.attunement == Attunement::NotRequired )
+            .map( |(_, item)| item )
+            .collect()
+    }
+}
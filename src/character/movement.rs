@@ -0,0 +1,80 @@
+use super::{ Character, Ability, Speed };
+use crate::datastore::{ Speeds, SpeedEffect, FeatEffect, FeatureEffect };
+use crate::error::Error;
+
+/// How much wearing armor without the strength it requires reduces speed
+const ARMOR_STRENGTH_PENALTY : Speed = 10;
+
+impl<'d> Character<'d> {
+    /// Returns the current speeds of the character, or throws an error if they have no race determining their base speeds.
+    /// Class features and feats can add to them, while heavy armor, carrying too much and some conditions
+    /// slow the character down.
+    pub fn speeds(&self) -> Result<Speeds, Error> {
+        let mut speeds = self.race_data()?.speed;
+        let effects = self.speed_effects();
+        for effect in &effects {
+            if let SpeedEffect::Bonus(mode, bonus) = effect {
+                let speed = speeds.get_mut(mode);
+                if *speed > 0 {
+                    *speed += bonus;
+                }
+            }
+        }
+        let walk = speeds.walk;
+        for effect in &effects {
+            match effect {
+                SpeedEffect::Grant(mode, granted) => {
+                    let speed = speeds.get_mut(mode);
+                    *speed = std::cmp::max(*speed, *granted);
+                },
+                SpeedEffect::EqualToWalking(mode) => {
+                    let speed = speeds.get_mut(mode);
+                    *speed = std::cmp::max(*speed, walk);
+                },
+                SpeedEffect::Hover => { speeds.hover = true; },
+                SpeedEffect::Bonus(_, _) => (),
+            }
+        }
+        if self.lacks_armor_strength()? {
+            speeds = speeds.map( |speed| speed.saturating_sub(ARMOR_STRENGTH_PENALTY) );
+        }
+        let encumbrance = self.encumbrance()?;
+        speeds = speeds.map( |speed| self.encumbered_speed(encumbrance, speed) );
+        speeds = speeds.map( |speed| self.conditional_speed(speed) );
+        Ok(speeds)
+    }
+    /// Returns the current walking speed of the character, or throws an error if they have no race determining their base speed
+    pub fn speed(&self) -> Result<Speed, Error> {
+        Ok(self.speeds()?.walk)
+    }
+    fn speed_effects(&self) -> Vec<SpeedEffect> {
+        let mut effects = Vec::new();
+        for feat in self.feats.iter().filter_map( |feat| self.data.get_feat(feat) ) {
+            for effect in &feat.effects {
+                if let FeatEffect::Speed(effect) = effect {
+                    effects.push(*effect);
+                }
+            }
+        }
+        for feature in self.features() {
+            for effect in &feature.effects {
+                if let FeatureEffect::Speed(effect) = effect {
+                    effects.push(*effect);
+                }
+            }
+        }
+        effects
+    }
+    /// Returns true if the character wears armor they are not strong enough for
+    fn lacks_armor_strength(&self) -> Result<bool, Error> {
+        let strength = *self.ability(&Ability::Str);
+        for (_, entry) in self.inventory.equipped() {
+            if let Some(armor) = self.entry_armor(entry)? {
+                if armor.strength_requirement.is_some_and( |requirement| strength < requirement ) {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,158 @@
+use super::{ Character, Ability, Die, Modifier, RollMode, EntryId };
+use crate::datastore::{ Weapon, Skill, RollKind, ConditionEffect, FeatEffect };
+use crate::error::Error;
+
+/// A source of random die rolls, so that e.g. tests or network games can decide how dice are rolled
+pub trait Roller {
+    /// Returns a number from 1 to the number of sides of the die
+    fn roll(&mut self, die : &Die) -> u8;
+}
+
+/// A small xorshift generator, which is random enough for rolling dice
+pub struct SimpleRoller {
+    state : u64,
+}
+
+/// The d20 rolls a character can make
+#[derive(Clone, Copy)]
+pub enum RollContext<'a> {
+    Check(Ability),
+    Skill(Ability, &'a Skill),
+    Save(Ability),
+    /// A saving throw against a kind of threat like "poison", which some traits grant advantage on
+    SaveAgainst(Ability, &'a str),
+    /// An attack with a mundane copy of the weapon
+    Attack(&'a Weapon),
+    /// An attack with a weapon from the inventory, which may be magic
+    AttackWith(EntryId),
+}
+
+/// Natural 20s and 1s on attack rolls always hit or miss
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Critical { None, Hit, Miss }
+
+/// The outcome of a d20 roll with everything that went into it
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct RollResult {
+    /// All d20s that were rolled, two if the roll had advantage or disadvantage
+    pub dice : Vec<u8>,
+    /// The d20 that counts
+    pub natural : u8,
+    pub mode : RollMode,
+    pub modifier : Modifier,
+    pub total : i16,
+    pub critical : Critical,
+}
+
+impl SimpleRoller {
+    pub fn new(seed : u64) -> Self {
+        // Xorshift never leaves a state of 0
+        SimpleRoller { state : if seed == 0 { 0x2545_f491_4f6c_dd1d } else { seed } }
+    }
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+impl Default for SimpleRoller {
+    /// Seeds the generator with the current time
+    fn default() -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64 );
+        SimpleRoller::new(seed)
+    }
+}
+
+impl Roller for SimpleRoller {
+    fn roll(&mut self, die : &Die) -> u8 {
+        ( self.next() % die.sides() as u64 ) as u8 + 1
+    }
+}
+
+impl<'d> Character<'d> {
+    /// Returns whether the character has advantage or disadvantage on the roll,
+    /// taking into account their conditions, armor, feats and traits affecting saving throws
+    pub fn roll_mode(&self, context : &RollContext) -> Result<RollMode, Error> {
+        let (condition_advantage, condition_disadvantage) = match context {
+            RollContext::Attack(_) | RollContext::AttackWith(_) => (ConditionEffect::AttackAdvantage, ConditionEffect::AttackDisadvantage),
+            RollContext::Check(_) | RollContext::Skill(_, _) => (ConditionEffect::CheckAdvantage, ConditionEffect::CheckDisadvantage),
+            RollContext::Save(_) | RollContext::SaveAgainst(_, _) => (ConditionEffect::SaveAdvantage, ConditionEffect::SaveDisadvantage),
+        };
+        let save_advantage = match context {
+            RollContext::Save(ability) => self.save_advantages(ability).contains(&None),
+            RollContext::SaveAgainst(ability, threat) => self.save_advantages(ability).iter()
+                .any( |against| against.as_deref().is_none_or( |against| against == *threat ) ),
+            _ => false,
+        };
+        let advantage = self.has_condition_effect(&condition_advantage)
+            || save_advantage
+            || self.feat_effects().any( |effect| match effect {
+                FeatEffect::Advantage(kind) => Character::roll_kind_applies(kind, context),
+                _ => false,
+            });
+        let disadvantage = self.has_condition_effect(&condition_disadvantage)
+            || ( matches!(context, RollContext::Skill(_, Skill::Stealth)) && self.wears_noisy_armor()? );
+        Ok(RollMode::from_sources(advantage, disadvantage))
+    }
+    /// Returns the modifier that is added to the d20 for the roll
+    pub fn roll_modifier(&self, context : &RollContext) -> Result<Modifier, Error> {
+        Ok(match context {
+            RollContext::Check(ability) => self.ability_check_mod(ability),
+            RollContext::Skill(ability, skill) => self.skill_mod(ability, skill)?,
+            RollContext::Save(ability) | RollContext::SaveAgainst(ability, _) => self.saving_throw(ability),
+            RollContext::Attack(weapon) => self.get_attack_mod(weapon),
+            RollContext::AttackWith(id) => self.entry_attack_mod(*id)?,
+        })
+    }
+    /// Rolls a d20 for the character, with advantage or disadvantage if they have it, and adds their modifier
+    pub fn roll(&self, context : &RollContext, roller : &mut dyn Roller) -> Result<RollResult, Error> {
+        let mode = self.roll_mode(context)?;
+        let modifier = self.roll_modifier(context)?;
+        let dice = match mode {
+            RollMode::Normal => vec![roller.roll(&Die::D20)],
+            RollMode::Advantage | RollMode::Disadvantage => vec![roller.roll(&Die::D20), roller.roll(&Die::D20)],
+        };
+        let natural = match mode {
+            RollMode::Disadvantage => *dice.iter().min().unwrap_or(&1),
+            _ => *dice.iter().max().unwrap_or(&1),
+        };
+        let critical = match (context, natural) {
+            (RollContext::Attack(_) | RollContext::AttackWith(_), 20) => Critical::Hit,
+            (RollContext::Attack(_) | RollContext::AttackWith(_), 1) => Critical::Miss,
+            _ => Critical::None,
+        };
+        Ok(RollResult { dice, natural, mode, modifier, total : natural as i16 + modifier as i16, critical })
+    }
+    pub(super) fn feat_effects(&self) -> impl Iterator<Item = &'d FeatEffect> + '_ {
+        let data = self.data;
+        self.feats.iter()
+            .filter_map( move |feat| data.get_feat(feat) )
+            .flat_map( |feat| feat.effects.iter() )
+    }
+    fn roll_kind_applies(kind : &RollKind, context : &RollContext) -> bool {
+        match (kind, context) {
+            (RollKind::Attack, RollContext::Attack(_) | RollContext::AttackWith(_)) => true,
+            (RollKind::Check(ability), RollContext::Check(rolled)) => ability == rolled,
+            (RollKind::Check(ability), RollContext::Skill(rolled, _)) => ability == rolled,
+            (RollKind::Skill(skill), RollContext::Skill(_, rolled)) => skill == *rolled,
+            (RollKind::Save(ability), RollContext::Save(rolled)) => ability == rolled,
+            (RollKind::Save(ability), RollContext::SaveAgainst(rolled, _)) => ability == rolled,
+            _ => false,
+        }
+    }
+    /// Returns whether the character wears armor that imposes disadvantage on stealth checks
+    fn wears_noisy_armor(&self) -> Result<bool, Error> {
+        for (_, entry) in self.inventory.equipped() {
+            if let Some(armor) = self.entry_armor(entry)? {
+                if armor.stealth_disadvantage {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
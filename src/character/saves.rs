@@ -0,0 +1,105 @@
+use super::{ Character, Ability, Modifier, ProficiencySource };
+use crate::datastore::{ SaveEffect, FeatEffect, FeatureEffect, MagicEffect, ConditionEffect };
+
+/// A row of the saving throws table on the character sheet
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SaveSummary {
+    pub ability : Ability,
+    /// Everything that makes the character proficient in the saving throw
+    pub proficiencies : Vec<ProficiencySource>,
+    pub modifier : Modifier,
+    /// What the character has advantage against, where None stands for every saving throw with the ability
+    pub advantages : Vec<Option<String>>,
+}
+
+impl<'d> Character<'d> {
+    /// Returns the modifier for saving throws with the ability, including the proficiency bonus
+    /// if any source makes the character proficient and bonuses from e.g. items or auras
+    pub fn saving_throw(&self, ability : &Ability) -> Modifier {
+        let proficiency = if self.save_proficiencies(ability).is_empty() { 0 } else { self.proficiency_bonus() };
+        let bonus : Modifier = self.save_effects().into_iter()
+            .filter_map( |(_, effect)| match effect {
+                SaveEffect::Bonus(bonus) => Some(bonus),
+                _ => None,
+            })
+            .sum();
+        let condition_modifier : Modifier = self.condition_effects().into_iter()
+            .filter_map( |effect| match effect {
+                ConditionEffect::SaveModifier(modifier) => Some(*modifier),
+                _ => None,
+            })
+            .sum();
+        Ability::score_to_mod(self.ability(ability)) + proficiency + bonus + condition_modifier
+    }
+    /// Returns every source that makes the character proficient in saving throws with the ability,
+    /// starting with their first class
+    pub fn save_proficiencies(&self, ability : &Ability) -> Vec<ProficiencySource> {
+        let mut sources = Vec::new();
+        if let Some(class) = self.primary_class() {
+            if class.saving_throws.contains(ability) {
+                sources.push(ProficiencySource::Class(class.name.clone()));
+            }
+        }
+        sources.extend(self.save_effects().into_iter()
+            .filter( |(_, effect)| *effect == SaveEffect::Proficiency(*ability) )
+            .map( |(source, _)| source ));
+        sources
+    }
+    /// Returns what the character has advantage against on saving throws with the ability,
+    /// where None stands for advantage on every saving throw with it
+    pub fn save_advantages(&self, ability : &Ability) -> Vec<Option<String>> {
+        let mut advantages = Vec::new();
+        for (_, effect) in self.save_effects() {
+            if let SaveEffect::Advantage(applies_to, against) = effect {
+                if ( applies_to.is_none() || applies_to == Some(*ability) ) && !advantages.contains(&against) {
+                    advantages.push(against);
+                }
+            }
+        }
+        advantages
+    }
+    /// Returns the saving throws with every ability for the character sheet
+    pub fn saves_table(&self) -> Vec<SaveSummary> {
+        [Ability::Str, Ability::Dex, Ability::Con, Ability::Int, Ability::Wis, Ability::Cha].iter()
+            .map( |ability| SaveSummary {
+                ability : *ability,
+                proficiencies : self.save_proficiencies(ability),
+                modifier : self.saving_throw(ability),
+                advantages : self.save_advantages(ability),
+            })
+            .collect()
+    }
+    /// Returns the effects on saving throws of the race, subrace, feats, class features and magic items
+    /// of the character together with where they come from
+    fn save_effects(&self) -> Vec<(ProficiencySource, SaveEffect)> {
+        let mut effects = Vec::new();
+        if let Some(race) = self.data.get_race(&self.race) {
+            effects.extend(race.save_effects.iter().map( |effect| (ProficiencySource::Race(race.name.clone()), effect.clone()) ));
+            if let Some(subrace) = race.get_subrace(&self.subrace) {
+                effects.extend(subrace.save_effects.iter().map( |effect| (ProficiencySource::Subrace(subrace.name.clone()), effect.clone()) ));
+            }
+        }
+        for feat in self.feats.iter().filter_map( |feat| self.data.get_feat(feat) ) {
+            for effect in &feat.effects {
+                if let FeatEffect::Save(effect) = effect {
+                    effects.push((ProficiencySource::Feat(feat.name.clone()), effect.clone()));
+                }
+            }
+        }
+        for feature in self.features() {
+            for effect in &feature.effects {
+                if let FeatureEffect::Save(effect) = effect {
+                    effects.push((ProficiencySource::Feature(feature.name.clone()), effect.clone()));
+                }
+            }
+        }
+        for item in self.active_magic_items() {
+            for effect in &item.effects {
+                if let MagicEffect::Save(effect) = effect {
+                    effects.push((ProficiencySource::Item(item.name.clone()), effect.clone()));
+                }
+            }
+        }
+        effects
+    }
+}
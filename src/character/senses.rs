@@ -0,0 +1,50 @@
+use super::{ Character, Modifier, RollContext, RollMode };
+use crate::datastore::{ Skill, FeatEffect };
+use crate::error::Error;
+
+/// Passive scores start from this value instead of a d20 roll
+const PASSIVE_BASE : Modifier = 10;
+/// How much advantage or disadvantage on the check changes a passive score
+const PASSIVE_ADVANTAGE : Modifier = 5;
+
+/// What the character notices without actively looking, as listed on the character sheet
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Senses {
+    /// The range of darkvision in feet, if the character has any
+    pub darkvision : Option<u16>,
+    pub passive_perception : Modifier,
+    pub passive_investigation : Modifier,
+    pub passive_insight : Modifier,
+}
+
+impl<'d> Character<'d> {
+    /// Returns the passive score for the skill, which is 10 plus the skill modifier, adjusted by 5
+    /// for advantage or disadvantage on checks with the skill
+    pub fn passive_score(&self, skill : &Skill) -> Result<Modifier, Error> {
+        let ability = skill.default_ability();
+        let adjustment = match self.roll_mode(&RollContext::Skill(ability, skill))? {
+            RollMode::Normal => 0,
+            RollMode::Advantage => PASSIVE_ADVANTAGE,
+            RollMode::Disadvantage => -PASSIVE_ADVANTAGE,
+        };
+        Ok(PASSIVE_BASE + self.skill_mod(&ability, skill)? + adjustment)
+    }
+    pub fn senses(&self) -> Result<Senses, Error> {
+        Ok(Senses {
+            darkvision : self.darkvision(),
+            passive_perception : self.passive_score(&Skill::Perception)?,
+            passive_investigation : self.passive_score(&Skill::Investigation)?,
+            passive_insight : self.passive_score(&Skill::Insight)?,
+        })
+    }
+    /// Returns false if a feat like Alert keeps the character from being surprised
+    pub fn can_be_surprised(&self) -> bool {
+        !self.feat_effects().any( |effect| *effect == FeatEffect::CannotBeSurprised )
+    }
+    /// Returns the bonus to initiative from feats like Alert
+    pub(super) fn feat_initiative_bonus(&self) -> Modifier {
+        self.feat_effects()
+            .filter_map( |effect| match effect { FeatEffect::InitiativeBonus(bonus) => Some(*bonus), _ => None } )
+            .sum()
+    }
+}
@@ -0,0 +1,93 @@
+use super::{ Character, ItemKind, EntryId, Currency, Edit, History };
+use crate::error::Error;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// A merchant with a limited stock of items from the datastore, who also buys items from characters
+pub struct Shop {
+    stock : HashMap<(ItemKind, String), u32>,
+    /// The share of the price, in percent, the shop pays for items it buys
+    buy_back_rate : u32,
+}
+
+impl Shop {
+    pub fn new(buy_back_rate : u32) -> Self {
+        Shop {
+            stock : HashMap::new(),
+            buy_back_rate,
+        }
+    }
+    pub fn add_stock(&mut self, kind : ItemKind, name : &str, quantity : u32) {
+        *self.stock.entry((kind, name.to_owned())).or_insert(0) += quantity;
+    }
+    /// Returns how many of the item the shop has for sale
+    pub fn stock(&self, kind : ItemKind, name : &str) -> u32 {
+        *self.stock.get(&(kind, name.to_owned())).unwrap_or(&0)
+    }
+    fn remove_stock(&mut self, kind : ItemKind, name : &str, quantity : u32) {
+        let key = (kind, name.to_owned());
+        if let Some(stock) = self.stock.get_mut(&key) {
+            *stock -= quantity;
+            if *stock == 0 {
+                self.stock.remove(&key);
+            }
+        }
+    }
+}
+
+impl<'d> Character<'d> {
+    /// Buys items from the shop at their price in the datastore and records the purchase in the history.
+    /// The items are only taken out of the stock of the shop once the purchase succeeded,
+    /// and undoing the purchase later does not put them back.
+    pub fn buy(&mut self, history : &mut History, shop : &mut Shop, kind : ItemKind, name : &str, quantity : u32) -> Result<(), Error> {
+        let in_stock = shop.stock(kind, name);
+        if in_stock < quantity {
+            return Err(Error::Validation(format!("The shop has only {} of \"{}\".", in_stock, name)));
+        }
+        let price = self.item_price(kind, name)?.value().checked_mul(u64::from(quantity))
+            .and_then( |price| u32::try_from(price).ok() );
+        let price = match price {
+            Some(price) => Currency::from_copper(price),
+            None => { return Err(Error::Validation("The price is too high.".to_owned())); },
+        };
+        history.apply(self, Edit::Buy(kind, name.to_owned(), quantity, price))?;
+        shop.remove_stock(kind, name, quantity);
+        Ok(())
+    }
+    /// Sells items of an entry to the shop and records the sale in the history.
+    /// The items are only added to the stock of the shop once the sale succeeded,
+    /// and undoing the sale later does not take them out again.
+    pub fn sell(&mut self, history : &mut History, shop : &mut Shop, id : EntryId, quantity : u32) -> Result<(), Error> {
+        let entry = self.inventory.get(id)?;
+        let (kind, name) = (entry.kind(), entry.name().to_owned());
+        if quantity > entry.quantity() {
+            return Err(Error::Validation(format!("There are only {} of \"{}\".", entry.quantity(), name)));
+        }
+        let payment = self.item_price(kind, &name)?.value().checked_mul(u64::from(quantity))
+            .and_then( |value| value.checked_mul(u64::from(shop.buy_back_rate)) )
+            .and_then( |value| u32::try_from(value / 100).ok() );
+        let payment = match payment {
+            Some(payment) => Currency::from_copper(payment),
+            None => { return Err(Error::Validation("The price is too high.".to_owned())); },
+        };
+        history.apply(self, Edit::Sell(id, quantity, payment))?;
+        shop.add_stock(kind, &name, quantity);
+        Ok(())
+    }
+    /// Pays the price for the items and adds them to the inventory, failing without changes if either is not possible
+    pub(super) fn pay_for_items(&mut self, kind : ItemKind, name : &str, quantity : u32, price : &Currency) -> Result<EntryId, Error> {
+        // Paying from a copy first leaves the character unchanged if either step fails
+        let mut currency = *self.inventory.currency();
+        currency.pay(price)?;
+        let id = self.add_item(kind, name, quantity)?;
+        self.spend_currency(price)?;
+        Ok(id)
+    }
+    /// Removes the items from the inventory and receives the payment for them
+    pub(super) fn get_paid_for_items(&mut self, id : EntryId, quantity : u32, payment : &Currency) -> Result<(), Error> {
+        let mut currency = *self.inventory.currency();
+        currency.add(payment)?;
+        self.remove_item(id, quantity)?;
+        self.gain_currency(payment)
+    }
+}
@@ -0,0 +1,98 @@
+use super::{ Character, ItemKind, Currency };
+use crate::datastore::{ Background, StartingEquipment, StartingItem };
+use crate::error::{ Error, DataKind };
+
+/// How a character takes the starting equipment of their class or background
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum EquipmentChoice {
+    /// The index of the chosen bundle for each choice, and the names of the weapons
+    /// picked for items that can be any weapon of a category, in the order they appear
+    Equipment { bundles : Vec<usize>, weapons : Vec<String> },
+    /// Takes the gold alternative instead of any of the equipment
+    Gold,
+}
+
+impl<'d> Character<'d> {
+    pub fn set_background(&mut self, background : &Background) {
+        self.background = background.name.clone();
+    }
+    pub fn background(&self) -> Option<&str> {
+        if self.background.is_empty() { None } else { Some(&self.background) }
+    }
+    /// Adds the starting equipment of the first class and the background of the character to their
+    /// inventory. The background choice is needed exactly when the character has a background.
+    /// This can only be done once, and nothing is added if any of the choices is not valid.
+    pub fn take_starting_equipment(&mut self, class_choice : &EquipmentChoice, background_choice : Option<&EquipmentChoice>) -> Result<(), Error> {
+        if self.starting_equipment_taken {
+            return Err(Error::Validation("Character has already taken their starting equipment.".to_owned()));
+        }
+        let class = self.primary_class().ok_or(Error::NotChosen(DataKind::Class))?;
+        let mut items = Vec::new();
+        let mut currency = Currency::default();
+        self.resolve_starting_equipment(&class.starting_equipment, class_choice, &mut items, &mut currency)?;
+        match (self.background.is_empty(), background_choice) {
+            (true, None) => (),
+            (false, Some(background_choice)) => {
+                let background = self.background_data()?;
+                self.resolve_starting_equipment(&background.starting_equipment, background_choice, &mut items, &mut currency)?;
+            },
+            (true, Some(_)) => { return Err(Error::NotChosen(DataKind::Background)); },
+            (false, None) => {
+                return Err(Error::Validation("The starting equipment of the background has to be chosen as well.".to_owned()));
+            },
+        }
+        for (kind, name, quantity) in items {
+            self.add_item(kind, &name, quantity)?;
+        }
+        self.gain_currency(&currency)?;
+        self.starting_equipment_taken = true;
+        Ok(())
+    }
+    pub fn has_taken_starting_equipment(&self) -> bool {
+        self.starting_equipment_taken
+    }
+    fn background_data(&self) -> Result<&'d Background, Error> {
+        if self.background.is_empty() {
+            return Err(Error::NotChosen(DataKind::Background));
+        }
+        self.data.get_background(&self.background).ok_or_else( || Error::missing(DataKind::Background, &self.background) )
+    }
+    /// Collects the items and money a choice of starting equipment grants, checking that they all exist
+    fn resolve_starting_equipment(&self, equipment : &StartingEquipment, choice : &EquipmentChoice,
+                                  items : &mut Vec<(ItemKind, String, u32)>, currency : &mut Currency) -> Result<(), Error> {
+        let (bundles, weapons) = match choice {
+            EquipmentChoice::Gold => {
+                currency.add(&equipment.gold_alternative)?;
+                return Ok(());
+            },
+            EquipmentChoice::Equipment { bundles, weapons } => (bundles, weapons),
+        };
+        if bundles.len() != equipment.choices.len() {
+            return Err(Error::Validation(format!("Exactly {} bundles of starting equipment have to be chosen.", equipment.choices.len())));
+        }
+        let mut weapons = weapons.iter();
+        for (options, chosen) in equipment.choices.iter().zip(bundles) {
+            let bundle = options.get(*chosen).ok_or_else( || Error::Validation(format!("There is no bundle {} to choose.", chosen)) )?;
+            for item in bundle {
+                match item {
+                    StartingItem::Item(kind, name, quantity) => {
+                        self.item_weight(*kind, name)?;
+                        items.push((*kind, name.clone(), *quantity));
+                    },
+                    StartingItem::AnyWeapon(category) => {
+                        let name = weapons.next().ok_or_else( || Error::Validation(format!("A {:?} weapon has to be picked.", category)) )?;
+                        let weapon = self.data.get_weapon(name).ok_or_else( || Error::missing(DataKind::Weapon, name) )?;
+                        if weapon.category != *category {
+                            return Err(Error::Validation(format!("\"{}\" is not a {:?} weapon.", name, category)));
+                        }
+                        items.push((ItemKind::Weapon, name.clone(), 1));
+                    },
+                }
+            }
+        }
+        if weapons.next().is_some() {
+            return Err(Error::Validation("More weapons were picked than the starting equipment allows.".to_owned()));
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,14 @@
 use super::*;
-use crate::datastore::{ Race, WeaponCategory, ArmorCategory };
+use crate::datastore::{ Race, WeaponCategory, WeaponRange, ArmorCategory };
+use crate::datastore::{ Advancement, ExperienceTable, Item, POUND };
+use crate::datastore::{ MagicItem, Rarity, MagicItemBase, Attunement, AttunementRequirement, MagicEffect };
+use crate::datastore::{ StartingEquipment, StartingItem, Background };
+use crate::datastore::{ Condition, ConditionEffect, RollKind };
+use crate::datastore::{ Tool, ToolCategory, ToolProficiency, FeatureEffect, Rounding, SaveEffect };
+use crate::datastore::{ RacialTrait, InnateSpell, NaturalWeapon, DamageType, DamageDefense };
+use crate::datastore::{ Speeds, SpeedEffect, MovementMode };
+use crate::datastore::{ WeaponProperty, SpellAttack };
+use crate::error::{ Error, DataKind };
 
 #[cfg(test)]
 mod test_non_data_dependent_features {
@@ -38,26 +47,110 @@ mod test_non_data_dependent_features {
     fn test_learn_skills() {
         let data = Datastore::new();
         let mut ch = Character::new(&data);
-        assert_eq!(*ch.skill_level(&Skill::Perception), SkillLevel::None);
+        assert_eq!(ch.skill_level(&Skill::Perception).unwrap(), SkillLevel::None);
         ch.set_skill_level(&Skill::Perception, SkillLevel::Proficient);
-        assert_eq!(*ch.skill_level(&Skill::Perception), SkillLevel::Proficient);
+        assert_eq!(ch.skill_level(&Skill::Perception).unwrap(), SkillLevel::Proficient);
     }
     #[test]
-    fn test_learn_parametrized_skills() {
+    fn test_skill_modifiers() {
         let data = Datastore::new();
         let mut ch = Character::new(&data);
-        ch.set_skill_level(&Skill::Vehicle("Car".to_owned()), SkillLevel::Expert);
-        assert_eq!(*ch.skill_level(&Skill::Vehicle("Car".to_owned())), SkillLevel::Expert);
+        ch.set_skill_level(&Skill::Perception, SkillLevel::Proficient);
+        ch.set_ability(&Ability::Wis, 14);
+        assert_eq!(ch.skill_mod(&Ability::Wis, &Skill::Perception).unwrap(), 4);
+        ch.set_level(10).unwrap();
+        assert_eq!(ch.skill_mod(&Ability::Wis, &Skill::Perception).unwrap(), 6)
     }
     #[test]
-    fn test_skill_modifiers() {
+    fn test_experience_points() {
+        let data = Datastore::new();
+        let mut ch = Character::new(&data);
+        assert_eq!(ch.award_xp(299).unwrap(), 0);
+        assert_eq!(ch.award_xp(1).unwrap(), 1);
+        assert_eq!(ch.award_xp(2400).unwrap(), 3);
+        ch.set_level(4).unwrap();
+        assert_eq!(ch.available_levels(), 0);
+        assert!(ch.award_milestone().is_err());
+    }
+    #[test]
+    fn test_custom_experience_table() {
+        let mut data = Datastore::new();
+        data.set_experience_table(ExperienceTable { thresholds : vec![0, 10, 100] });
+        let mut ch = Character::new(&data);
+        assert_eq!(ch.award_xp(5000).unwrap(), 2);
+        assert_eq!(data.experience_table().experience_for(2), Ok(10));
+        assert_eq!(data.experience_table().experience_for(4), Err(Error::InvalidLevel(4)));
+    }
+    #[test]
+    fn test_milestones() {
+        let data = Datastore::new();
+        let mut ch = Character::new(&data);
+        ch.set_advancement(Advancement::Milestone);
+        assert!(ch.award_xp(1000).is_err());
+        assert_eq!(ch.award_milestone().unwrap(), 1);
+        ch.set_level(2).unwrap();
+        assert_eq!(ch.available_levels(), 0);
+    }
+    #[test]
+    fn test_milestones_above_first_level() {
+        let data = Datastore::new();
+        let mut ch = Character::new(&data);
+        ch.set_level(5).unwrap();
+        ch.set_advancement(Advancement::Milestone);
+        assert_eq!(ch.available_levels(), 0);
+        assert_eq!(ch.award_milestone().unwrap(), 1);
+        ch.set_level(6).unwrap();
+        assert_eq!(ch.available_levels(), 0);
+        assert_eq!(ch.award_milestone().unwrap(), 1);
+    }
+    #[test]
+    fn test_milestones_stop_at_highest_level() {
+        let data = Datastore::new();
+        let mut ch = Character::new(&data);
+        ch.set_advancement(Advancement::Milestone);
+        for _ in 1..20 {
+            ch.award_milestone().unwrap();
+        }
+        assert_eq!(ch.available_levels(), 19);
+        for _ in 0..200 {
+            assert_eq!(ch.award_milestone(), Err(Error::InvalidLevel(21)));
+        }
+        assert_eq!(ch.available_levels(), 19);
+    }
+    #[test]
+    fn test_multiclass_spell_slots() {
+        let (full, half, pact) = (SpellProgression::full(), SpellProgression::half(), SpellProgression::pact());
+        assert_eq!(
+            SpellProgression::multiclass_slots(&[(3, &half)], &full).unwrap(),
+            SpellLevel::slots(3,0,0,0,0,0,0,0,0)
+        );
+        assert_eq!(
+            SpellProgression::multiclass_slots(&[(3, &half), (3, &full), (5, &pact)], &full).unwrap(),
+            SpellLevel::slots(4,3,0,0,0,0,0,0,0)
+        );
+        assert_eq!(
+            SpellProgression::multiclass_short_rest_slots(&[(3, &half), (3, &full), (5, &pact)]).unwrap(),
+            SpellLevel::slots(0,0,2,0,0,0,0,0,0)
+        );
+    }
+    #[test]
+    fn test_invalid_levels_have_no_spell_slots() {
+        assert!(SpellProgression::full().slots_for_level(&0).is_err());
+        assert!(SpellProgression::full().slots_for_level(&21).is_err());
+    }
+    #[test]
+    fn test_skills_use_their_default_ability() {
         let data = Datastore::new();
         let mut ch = Character::new(&data);
-        ch.set_skill_level(&Skill::Perception, SkillLevel::Proficient);
         ch.set_ability(&Ability::Wis, 14);
-        assert_eq!(ch.skill_mod(&Ability::Wis, &Skill::Perception), 4);
-        ch.set_level(10);
-        assert_eq!(ch.skill_mod(&Ability::Wis, &Skill::Perception), 6)
+        ch.set_ability(&Ability::Str, 8);
+        assert_eq!(ch.skill_mod_default(&Skill::Perception).unwrap(), 2);
+        assert_eq!(ch.skill_mod(&Ability::Str, &Skill::Perception).unwrap(), -1);
+        ch.set_skill_level(&Skill::Athletics, SkillLevel::Expert);
+        let table = ch.skill_table().unwrap();
+        assert_eq!(table.len(), 18);
+        assert!(table.iter().all( |row| row.skill.default_ability() == row.ability ));
+        assert_eq!(table[0], SkillSummary { skill : Skill::Athletics, ability : Ability::Str, level : SkillLevel::Expert, modifier : 3 });
     }
 }
 
@@ -72,10 +165,10 @@ mod test_race_data_dependent_features {
         ch.set_race(angel).unwrap();
         assert_eq!(*ch.ability(&Ability::Wis), 12);
         assert_eq!(*ch.size().unwrap(), Size::Medium);
-        assert_eq!(*ch.speed().unwrap(), 40);
+        assert_eq!(ch.speed().unwrap(), 40);
         assert!(ch.speaks("Angelic"));
         assert!(!ch.speaks("Demonic"));
-        assert_eq!(*ch.skill_level(&Skill::Persuasion), SkillLevel::Proficient);
+        assert_eq!(ch.skill_level(&Skill::Persuasion).unwrap(), SkillLevel::Proficient);
     }
     #[test]
     fn test_setting_different_races_undoes_effects_of_first_race() {
@@ -88,11 +181,11 @@ mod test_race_data_dependent_features {
         assert_eq!(*ch.ability(&Ability::Con), 12);
         assert_eq!(*ch.ability(&Ability::Wis), 10);
         assert_eq!(*ch.size().unwrap(), Size::Large);
-        assert_eq!(*ch.speed().unwrap(), 30);
+        assert_eq!(ch.speed().unwrap(), 30);
         assert!(!ch.speaks("Angelic"));
         assert!(ch.speaks("Demonic"));
-        assert_eq!(*ch.skill_level(&Skill::Persuasion), SkillLevel::None);
-        assert_eq!(*ch.skill_level(&Skill::Intimidation), SkillLevel::Proficient);
+        assert_eq!(ch.skill_level(&Skill::Persuasion).unwrap(), SkillLevel::None);
+        assert_eq!(ch.skill_level(&Skill::Intimidation).unwrap(), SkillLevel::Proficient);
     }
     fn datastore_with_test_races() -> Datastore {
         let mut data = Datastore::new();
@@ -138,6 +231,368 @@ mod test_equipment_data_dependent_features {
         assert!(ch.can_equip(armor));
     }
 
+    #[test]
+    fn test_purchases_survive_undo() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        let mut shop = Shop::new(50);
+        shop.add_stock(ItemKind::Item, "Arrow", 20);
+        history.apply(&mut ch, Edit::GainCurrency(Currency { gp : 100, ..Currency::default() })).unwrap();
+        history.apply(&mut ch, Edit::SetName("Shopper".to_owned())).unwrap();
+        ch.buy(&mut history, &mut shop, ItemKind::Item, "Arrow", 10).unwrap();
+        ch.sell(&mut history, &mut shop, 0, 4).unwrap();
+        assert_eq!(shop.stock(ItemKind::Item, "Arrow"), 14);
+        history.undo(&mut ch).unwrap();
+        assert_eq!(ch.inventory().entry(0).unwrap().quantity(), 10);
+        assert_eq!(*ch.inventory().currency(), Currency { gp : 99, sp : 5, ..Currency::default() });
+        history.undo(&mut ch).unwrap();
+        assert!(ch.inventory().entry(0).is_none());
+        assert_eq!(shop.stock(ItemKind::Item, "Arrow"), 14);
+        history.redo(&mut ch).unwrap();
+        history.redo(&mut ch).unwrap();
+        assert_eq!(ch.inventory().entry(0).unwrap().quantity(), 6);
+    }
+    #[test]
+    fn test_failed_purchase_keeps_stock() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        let mut shop = Shop::new(50);
+        shop.add_stock(ItemKind::Item, "Arrow", 20);
+        assert_eq!(ch.buy(&mut history, &mut shop, ItemKind::Item, "Arrow", 5), Err(Error::InsufficientFunds));
+        assert_eq!(shop.stock(ItemKind::Item, "Arrow"), 20);
+        assert!(ch.inventory().entries().is_empty());
+        assert!(!history.undo(&mut ch).unwrap());
+    }
+    #[test]
+    fn test_huge_prices_do_not_overflow() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        let mut shop = Shop::new(50);
+        shop.add_stock(ItemKind::Armor, "Power Armor", u32::MAX);
+        assert!(ch.buy(&mut history, &mut shop, ItemKind::Armor, "Power Armor", u32::MAX).is_err());
+        let armor = ch.add_item(ItemKind::Armor, "Power Armor", u32::MAX).unwrap();
+        assert!(ch.sell(&mut history, &mut shop, armor, u32::MAX).is_err());
+        assert_eq!(ch.carried_weight().unwrap(), u32::MAX);
+        assert!(ch.add_item(ItemKind::Armor, "Power Armor", 1).is_err());
+        assert_eq!(ch.inventory().entry(armor).unwrap().quantity(), u32::MAX);
+    }
+
+    fn data_store_with_equipment() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_equipment(data);
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_inventory {
+    use super::*;
+    #[test]
+    fn test_adding_and_removing_items() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let arrows = ch.add_item(ItemKind::Item, "Arrow", 20).unwrap();
+        assert_eq!(ch.add_item(ItemKind::Item, "Arrow", 10).unwrap(), arrows);
+        assert_eq!(ch.inventory().entry(arrows).unwrap().quantity(), 30);
+        assert_eq!(ch.add_item(ItemKind::Weapon, "Rope", 1), Err(Error::missing(DataKind::Weapon, "Rope")));
+        assert!(ch.remove_item(arrows, 31).is_err());
+        ch.remove_item(arrows, 30).unwrap();
+        assert!(ch.inventory().entries().is_empty());
+        assert_eq!(ch.remove_item(arrows, 1), Err(Error::UnknownInventoryEntry(arrows)));
+    }
+    #[test]
+    fn test_containers() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let backpack = ch.add_item(ItemKind::Item, "Backpack", 1).unwrap();
+        let rope = ch.add_item(ItemKind::Item, "Rope", 4).unwrap();
+        assert!(ch.move_item(backpack, Some(rope)).is_err());
+        assert!(ch.move_item(rope, Some(backpack)).is_err());
+        ch.remove_item(rope, 1).unwrap();
+        ch.move_item(rope, Some(backpack)).unwrap();
+        assert!(ch.move_item(backpack, Some(backpack)).is_err());
+        assert_eq!(ch.inventory().contents(Some(backpack)).len(), 1);
+        assert!(ch.equip(rope).is_err());
+        ch.remove_item(backpack, 1).unwrap();
+        assert_eq!(ch.inventory().entry(rope).unwrap().container(), None);
+    }
+    #[test]
+    fn test_equipping_armor() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let power_armor = ch.add_item(ItemKind::Armor, "Power Armor", 1).unwrap();
+        let mithril = ch.add_item(ItemKind::Armor, "Mithril Mail", 1).unwrap();
+        let shield = ch.add_item(ItemKind::Armor, "Tower Shield", 1).unwrap();
+        ch.equip(power_armor).unwrap();
+        ch.equip(shield).unwrap();
+        assert_eq!(ch.equip(mithril), Err(Error::AlreadyEquipped("Power Armor".to_owned())));
+        ch.unequip(power_armor).unwrap();
+        ch.equip(mithril).unwrap();
+        assert_eq!(ch.inventory().equipped().len(), 2);
+    }
+    #[test]
+    fn test_attunement_slots() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let mut items : Vec<EntryId> = Vec::new();
+        for _ in 0..ATTUNEMENT_SLOTS {
+            let ring = ch.add_item(ItemKind::MagicItem, "Ring of Fire Resistance", 1).unwrap();
+            ch.attune(ring).unwrap();
+            items.push(ring);
+        }
+        let ring = ch.add_item(ItemKind::MagicItem, "Ring of Fire Resistance", 1).unwrap();
+        assert_eq!(ch.attune(ring), Err(Error::AttunementLimit));
+        ch.end_attunement(items[0]).unwrap();
+        ch.attune(ring).unwrap();
+    }
+    #[test]
+    fn test_only_magic_items_requiring_it_can_be_attuned() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let rope = ch.add_item(ItemKind::Item, "Rope", 1).unwrap();
+        assert_eq!(ch.attune(rope), Err(Error::Validation("\"Rope\" is not a magic item.".to_owned())));
+        let sword = ch.add_item(ItemKind::MagicItem, "Bloodsword +2", 1).unwrap();
+        assert!(ch.attune(sword).is_err());
+        assert!(ch.inventory().attuned().is_empty());
+    }
+    #[test]
+    fn test_carried_weight() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let backpack = ch.add_item(ItemKind::Item, "Backpack", 1).unwrap();
+        let arrows = ch.add_item(ItemKind::Item, "Arrow", 20).unwrap();
+        ch.move_item(arrows, Some(backpack)).unwrap();
+        ch.gain_currency(&Currency { gp : 40, sp : 10, ..Currency::default() }).unwrap();
+        assert_eq!(ch.carried_weight().unwrap(), 7 * POUND);
+        assert_eq!(ch.inventory().currency().gp, 40);
+    }
+
+    fn data_store_with_equipment() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_magic_items(add_equipment(data));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_magic_items {
+    use super::*;
+    #[test]
+    fn test_magic_weapon_needs_to_be_equipped() {
+        let data = data_store_with_classes_equipment_and_magic_items();
+        let mut ch = Character::new(&data);
+        let bloodsword = data.get_weapon("Bloodsword").unwrap();
+        let sword = ch.add_item(ItemKind::MagicItem, "Bloodsword +2", 1).unwrap();
+        assert_eq!(ch.carried_weight().unwrap(), 3 * POUND);
+        assert_eq!(ch.entry_attack_mod(sword).unwrap(), 0);
+        ch.equip(sword).unwrap();
+        assert_eq!(ch.entry_attack_mod(sword).unwrap(), 2);
+        assert_eq!(ch.roll_modifier(&RollContext::AttackWith(sword)).unwrap(), 2);
+        assert_eq!(ch.get_attack_mod(data.get_weapon("Beau's Bow").unwrap()), 0);
+        assert_eq!(ch.get_attack_mod(bloodsword), 0);
+    }
+    #[test]
+    fn test_mundane_copy_gets_no_magic_bonus() {
+        let data = data_store_with_classes_equipment_and_magic_items();
+        let mut ch = Character::new(&data);
+        let magic_sword = ch.add_item(ItemKind::MagicItem, "Bloodsword +2", 1).unwrap();
+        let mundane_sword = ch.add_item(ItemKind::Weapon, "Bloodsword", 1).unwrap();
+        ch.equip(magic_sword).unwrap();
+        ch.equip(mundane_sword).unwrap();
+        assert_eq!(ch.entry_attack_mod(magic_sword).unwrap(), 2);
+        assert_eq!(ch.entry_attack_mod(mundane_sword).unwrap(), 0);
+        let backpack = ch.add_item(ItemKind::Item, "Backpack", 1).unwrap();
+        assert!(ch.entry_attack_mod(backpack).is_err());
+    }
+    #[test]
+    fn test_armor_class() {
+        let data = data_store_with_classes_equipment_and_magic_items();
+        let mut ch = Character::new(&data);
+        ch.set_ability(&Ability::Dex, 16);
+        assert_eq!(ch.armor_class().unwrap(), 13);
+        let mithril = ch.add_item(ItemKind::MagicItem, "Mithril Mail +1", 1).unwrap();
+        let shield = ch.add_item(ItemKind::Armor, "Tower Shield", 1).unwrap();
+        ch.equip(mithril).unwrap();
+        ch.equip(shield).unwrap();
+        assert_eq!(ch.armor_class().unwrap(), 19);
+        let power_armor = ch.add_item(ItemKind::Armor, "Power Armor", 1).unwrap();
+        assert!(ch.equip(power_armor).is_err());
+        ch.unequip(mithril).unwrap();
+        ch.equip(power_armor).unwrap();
+        assert_eq!(ch.armor_class().unwrap(), 20);
+    }
+    #[test]
+    fn test_ability_override_requires_attunement() {
+        let data = data_store_with_classes_equipment_and_magic_items();
+        let mut ch = Character::new(&data);
+        ch.set_ability(&Ability::Str, 8);
+        let gauntlets = ch.add_item(ItemKind::MagicItem, "Gauntlets of Ogre Power", 1).unwrap();
+        ch.equip(gauntlets).unwrap();
+        assert_eq!(*ch.ability(&Ability::Str), 8);
+        ch.attune(gauntlets).unwrap();
+        assert_eq!(*ch.ability(&Ability::Str), 19);
+        ch.set_ability(&Ability::Str, 20);
+        assert_eq!(*ch.ability(&Ability::Str), 20);
+        ch.set_ability(&Ability::Str, 8);
+        ch.unequip(gauntlets).unwrap();
+        assert_eq!(*ch.ability(&Ability::Str), 8);
+    }
+    #[test]
+    fn test_skill_bonus_and_spells() {
+        let data = data_store_with_classes_equipment_and_magic_items();
+        let mut ch = Character::new(&data);
+        let cloak = ch.add_item(ItemKind::MagicItem, "Cloak of Sneaking", 1).unwrap();
+        assert!(ch.attune(cloak).is_err());
+        ch.equip(cloak).unwrap();
+        assert_eq!(ch.skill_mod(&Ability::Dex, &Skill::Stealth).unwrap(), 2);
+        assert_eq!(ch.skill_mod(&Ability::Dex, &Skill::Acrobatics).unwrap(), 0);
+        assert_eq!(ch.item_spells(), vec!["Magic Boot"]);
+    }
+    #[test]
+    fn test_attunement_restricted_by_class() {
+        let data = data_store_with_classes_equipment_and_magic_items();
+        let mut ch = Character::new(&data);
+        let staff = ch.add_item(ItemKind::MagicItem, "Staff of the Mage", 1).unwrap();
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        assert!(ch.attune(staff).is_err());
+        ch.set_class(data.get_class("Mage").unwrap()).unwrap();
+        ch.attune(staff).unwrap();
+        assert_eq!(ch.inventory().attuned().len(), 1);
+    }
+
+    fn data_store_with_classes_equipment_and_magic_items() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_magic_items(add_equipment(add_classes(data)));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_starting_equipment {
+    use super::*;
+    #[test]
+    fn test_taking_starting_equipment() {
+        let data = data_store_with_classes_backgrounds_and_equipment();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        let class_choice = EquipmentChoice::Equipment { bundles : vec![0, 0], weapons : vec!["Bloodsword".to_owned()] };
+        let background_choice = EquipmentChoice::Equipment { bundles : vec![0], weapons : vec![] };
+        assert_eq!(ch.take_starting_equipment(&class_choice, Some(&background_choice)), Err(Error::NotChosen(DataKind::Background)));
+        ch.set_background(data.get_background("Sailor").unwrap());
+        ch.take_starting_equipment(&class_choice, Some(&background_choice)).unwrap();
+        assert!(ch.has_taken_starting_equipment());
+        assert_eq!(ch.carried_weight().unwrap(), 33 * POUND);
+        assert!(ch.take_starting_equipment(&class_choice, Some(&background_choice)).is_err());
+    }
+    #[test]
+    fn test_starting_equipment_choices_are_validated() {
+        let data = data_store_with_classes_backgrounds_and_equipment();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        ch.set_background(data.get_background("Sailor").unwrap());
+        let background_choice = EquipmentChoice::Equipment { bundles : vec![0], weapons : vec![] };
+        for bundles in [vec![0], vec![2, 0]] {
+            let class_choice = EquipmentChoice::Equipment { bundles, weapons : vec!["Bloodsword".to_owned()] };
+            assert!(ch.take_starting_equipment(&class_choice, Some(&background_choice)).is_err());
+        }
+        let simple_weapon = EquipmentChoice::Equipment { bundles : vec![0, 0], weapons : vec!["Beau's Bow".to_owned()] };
+        assert!(ch.take_starting_equipment(&simple_weapon, Some(&background_choice)).is_err());
+        let extra_weapon = EquipmentChoice::Equipment { bundles : vec![1, 0], weapons : vec!["Bloodsword".to_owned()] };
+        assert!(ch.take_starting_equipment(&extra_weapon, Some(&background_choice)).is_err());
+        assert!(ch.inventory().entries().is_empty());
+        assert!(!ch.has_taken_starting_equipment());
+    }
+    #[test]
+    fn test_taking_gold_instead() {
+        let data = data_store_with_classes_backgrounds_and_equipment();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        ch.set_background(data.get_background("Sailor").unwrap());
+        let class_choice = EquipmentChoice::Equipment { bundles : vec![1, 0], weapons : vec![] };
+        ch.take_starting_equipment(&class_choice, Some(&EquipmentChoice::Gold)).unwrap();
+        assert_eq!(ch.inventory().entries().len(), 2);
+        assert_eq!(ch.inventory().currency().gp, 10);
+    }
+    #[test]
+    fn test_starting_equipment_without_background() {
+        let data = data_store_with_classes_backgrounds_and_equipment();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        let class_choice = EquipmentChoice::Equipment { bundles : vec![1, 0], weapons : vec![] };
+        ch.take_starting_equipment(&class_choice, None).unwrap();
+        assert_eq!(ch.inventory().entries().len(), 2);
+        assert!(ch.has_taken_starting_equipment());
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        ch.set_background(data.get_background("Sailor").unwrap());
+        assert!(ch.take_starting_equipment(&class_choice, None).is_err());
+        assert!(!ch.has_taken_starting_equipment());
+    }
+
+    fn data_store_with_classes_backgrounds_and_equipment() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_backgrounds(add_equipment(add_classes(data)));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_shop {
+    use super::*;
+    #[test]
+    fn test_paying_with_change() {
+        let mut wallet = Currency { gp : 1, sp : 2, cp : 3, ..Currency::default() };
+        wallet.pay(&Currency { sp : 2, cp : 5, ..Currency::default() }).unwrap();
+        assert_eq!(wallet, Currency { sp : 9, cp : 8, ..Currency::default() });
+        assert_eq!(wallet.pay(&Currency { gp : 1, ..Currency::default() }), Err(Error::InsufficientFunds));
+        let mut wallet = Currency { pp : 1, ..Currency::default() };
+        wallet.pay(&Currency { ep : 1, ..Currency::default() }).unwrap();
+        assert_eq!(wallet, Currency { gp : 9, sp : 5, ..Currency::default() });
+    }
+    #[test]
+    fn test_large_amounts_of_money() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        let mut shop = Shop::new(50);
+        shop.add_stock(ItemKind::Item, "Arrow", 20);
+        ch.gain_currency(&Currency { pp : 5_000_000, ..Currency::default() }).unwrap();
+        assert_eq!(ch.inventory().currency().value(), 5_000_000_000);
+        ch.spend_currency(&Currency { gp : 1, ..Currency::default() }).unwrap();
+        ch.buy(&mut history, &mut shop, ItemKind::Item, "Arrow", 10).unwrap();
+        let before = *ch.inventory().currency();
+        assert!(ch.gain_currency(&Currency { cp : 1, pp : u32::MAX, ..Currency::default() }).is_err());
+        assert_eq!(*ch.inventory().currency(), before);
+    }
+    #[test]
+    fn test_buying_and_selling() {
+        let data = data_store_with_equipment();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        let mut shop = Shop::new(50);
+        shop.add_stock(ItemKind::Weapon, "Bloodsword", 1);
+        shop.add_stock(ItemKind::Item, "Arrow", 20);
+        ch.gain_currency(&Currency { gp : 16, ..Currency::default() }).unwrap();
+        assert!(ch.buy(&mut history, &mut shop, ItemKind::Weapon, "Bloodsword", 2).is_err());
+        ch.buy(&mut history, &mut shop, ItemKind::Weapon, "Bloodsword", 1).unwrap();
+        let sword = 0;
+        assert_eq!(shop.stock(ItemKind::Weapon, "Bloodsword"), 0);
+        assert_eq!(*ch.inventory().currency(), Currency { gp : 1, ..Currency::default() });
+        ch.buy(&mut history, &mut shop, ItemKind::Item, "Arrow", 10).unwrap();
+        let arrows = 1;
+        assert_eq!(*ch.inventory().currency(), Currency { sp : 5, ..Currency::default() });
+        assert!(ch.buy(&mut history, &mut shop, ItemKind::Item, "Arrow", 11).is_err());
+        assert_eq!(shop.stock(ItemKind::Item, "Arrow"), 10);
+        assert_eq!(ch.inventory().entry(arrows).unwrap().quantity(), 10);
+        ch.sell(&mut history, &mut shop, sword, 1).unwrap();
+        assert!(ch.inventory().entry(sword).is_none());
+        assert_eq!(shop.stock(ItemKind::Weapon, "Bloodsword"), 1);
+        assert_eq!(ch.inventory().currency().value(), 800);
+    }
+
     fn data_store_with_equipment() -> Datastore {
         let mut data = Datastore::new();
         data = add_equipment(data);
@@ -145,6 +600,709 @@ mod test_equipment_data_dependent_features {
     }
 }
 
+#[cfg(test)]
+mod test_conditions {
+    use super::*;
+    #[test]
+    fn test_conditions_affect_rolls() {
+        let data = data_store_with_races_equipment_and_conditions();
+        let mut ch = Character::new(&data);
+        assert_eq!(ch.attack_roll_mode(), RollMode::Normal);
+        ch.add_condition(data.get_condition("Poisoned").unwrap(), Duration::Indefinite).unwrap();
+        assert!(ch.has_condition("Poisoned"));
+        assert_eq!(ch.attack_roll_mode(), RollMode::Disadvantage);
+        assert_eq!(ch.check_roll_mode(), RollMode::Disadvantage);
+        assert_eq!(ch.save_roll_mode(), RollMode::Normal);
+        ch.add_condition(data.get_condition("Invisible").unwrap(), Duration::Indefinite).unwrap();
+        assert_eq!(ch.attack_roll_mode(), RollMode::Normal);
+        ch.remove_condition("Poisoned");
+        assert_eq!(ch.attack_roll_mode(), RollMode::Advantage);
+    }
+    #[test]
+    fn test_conditions_affect_modifiers() {
+        let data = data_store_with_races_equipment_and_conditions();
+        let mut ch = Character::new(&data);
+        let bloodsword = data.get_weapon("Bloodsword").unwrap();
+        ch.add_condition(data.get_condition("Cursed").unwrap(), Duration::UntilLongRest).unwrap();
+        assert_eq!(ch.get_attack_mod(bloodsword), -1);
+        assert_eq!(ch.skill_mod(&Ability::Wis, &Skill::Perception).unwrap(), -2);
+        ch.long_rest();
+        assert_eq!(ch.get_attack_mod(bloodsword), 0);
+    }
+    #[test]
+    fn test_conditions_expire() {
+        let data = data_store_with_races_equipment_and_conditions();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        ch.add_condition(data.get_condition("Restrained").unwrap(), Duration::Rounds(2)).unwrap();
+        assert_eq!(ch.speed().unwrap(), 0);
+        ch.pass_rounds(1);
+        assert_eq!(ch.conditions()[0].duration(), Duration::Rounds(1));
+        ch.pass_rounds(1);
+        assert!(ch.conditions().is_empty());
+        assert_eq!(ch.speed().unwrap(), 40);
+    }
+    #[test]
+    fn test_exhaustion() {
+        let data = data_store_with_races_equipment_and_conditions();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        ch.gain_exhaustion().unwrap();
+        assert_eq!(ch.check_roll_mode(), RollMode::Disadvantage);
+        assert_eq!(ch.speed().unwrap(), 40);
+        ch.gain_exhaustion().unwrap();
+        assert_eq!(ch.speed().unwrap(), 20);
+        for _ in 2..6 {
+            ch.gain_exhaustion().unwrap();
+        }
+        assert!(ch.gain_exhaustion().is_err());
+        assert_eq!(ch.speed().unwrap(), 0);
+        assert_eq!(ch.save_roll_mode(), RollMode::Disadvantage);
+        ch.long_rest();
+        assert_eq!(ch.exhaustion(), 5);
+    }
+    #[test]
+    fn test_conditions_survive_undo() {
+        let data = data_store_with_races_equipment_and_conditions();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        history.apply(&mut ch, Edit::SetRace("Angel".to_owned())).unwrap();
+        ch.add_condition(data.get_condition("Poisoned").unwrap(), Duration::Indefinite).unwrap();
+        ch.gain_exhaustion().unwrap();
+        history.apply(&mut ch, Edit::SetName("Dude".to_owned())).unwrap();
+        history.undo(&mut ch).unwrap();
+        assert!(ch.has_condition("Poisoned"));
+        assert_eq!(ch.exhaustion(), 1);
+    }
+
+    fn data_store_with_races_equipment_and_conditions() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_conditions(add_equipment(add_races(data)));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_rolls {
+    use super::*;
+    /// Rolls the given numbers in order
+    struct FixedRoller(Vec<u8>);
+    impl Roller for FixedRoller {
+        fn roll(&mut self, _die : &Die) -> u8 {
+            self.0.remove(0)
+        }
+    }
+    #[test]
+    fn test_simple_roller() {
+        let mut roller = SimpleRoller::new(42);
+        let rolls : Vec<u8> = (0..1000).map( |_| roller.roll(&Die::D20) ).collect();
+        assert!(rolls.iter().all( |roll| *roll >= 1 && *roll <= 20 ));
+        assert!(rolls.contains(&1) && rolls.contains(&20));
+        let mut same_seed = SimpleRoller::new(42);
+        assert_eq!(same_seed.roll(&Die::D20), rolls[0]);
+    }
+    #[test]
+    fn test_attack_rolls() {
+        let data = data_store_with_equipment_conditions_and_feats();
+        let mut ch = Character::new(&data);
+        ch.set_ability(&Ability::Str, 14);
+        let attack = RollContext::Attack(data.get_weapon("Bloodsword").unwrap());
+        let result = ch.roll(&attack, &mut FixedRoller(vec![20])).unwrap();
+        assert_eq!(result.mode, RollMode::Normal);
+        assert_eq!(result.total, 22);
+        assert_eq!(result.critical, Critical::Hit);
+        ch.add_condition(data.get_condition("Poisoned").unwrap(), Duration::Indefinite).unwrap();
+        let result = ch.roll(&attack, &mut FixedRoller(vec![20, 1])).unwrap();
+        assert_eq!(result.mode, RollMode::Disadvantage);
+        assert_eq!(result.dice, vec![20, 1]);
+        assert_eq!(result.natural, 1);
+        assert_eq!(result.critical, Critical::Miss);
+    }
+    #[test]
+    fn test_stealth_rolls() {
+        let data = data_store_with_equipment_conditions_and_feats();
+        let mut ch = Character::new(&data);
+        let stealth = RollContext::Skill(Ability::Dex, &Skill::Stealth);
+        let power_armor = ch.add_item(ItemKind::Armor, "Power Armor", 1).unwrap();
+        ch.equip(power_armor).unwrap();
+        assert_eq!(ch.roll_mode(&stealth).unwrap(), RollMode::Disadvantage);
+        assert_eq!(ch.roll_mode(&RollContext::Skill(Ability::Dex, &Skill::Acrobatics)).unwrap(), RollMode::Normal);
+        ch.learn_feat(data.get_feat("Sneaky").unwrap()).unwrap();
+        assert_eq!(ch.roll_mode(&stealth).unwrap(), RollMode::Normal);
+        ch.unequip(power_armor).unwrap();
+        let result = ch.roll(&stealth, &mut FixedRoller(vec![3, 17])).unwrap();
+        assert_eq!(result.natural, 17);
+        assert_eq!(result.critical, Critical::None);
+    }
+    #[test]
+    fn test_saving_throw_rolls() {
+        let data = data_store_with_equipment_conditions_and_feats();
+        let mut ch = Character::new(&data);
+        for _ in 0..3 {
+            ch.gain_exhaustion().unwrap();
+        }
+        let result = ch.roll(&RollContext::Save(Ability::Con), &mut FixedRoller(vec![12, 8])).unwrap();
+        assert_eq!(result.mode, RollMode::Disadvantage);
+        assert_eq!(result.total, 8);
+    }
+
+    fn data_store_with_equipment_conditions_and_feats() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_feats(add_conditions(add_equipment(data)));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_tools {
+    use super::*;
+    #[test]
+    fn test_tool_proficiencies() {
+        let data = data_store_with_tools();
+        let mut ch = Character::new(&data);
+        assert_eq!(ch.tool_level("Car"), Err(Error::missing(DataKind::Tool, "Car")));
+        ch.set_tool_level(data.get_tool("Sailboat").unwrap(), SkillLevel::Expert);
+        assert_eq!(ch.tool_level("Sailboat").unwrap(), SkillLevel::Expert);
+        ch.set_ability(&Ability::Dex, 14);
+        assert_eq!(ch.tool_mod_default("Thieves' Tools").unwrap(), 2);
+        assert_eq!(ch.tool_mod(&Ability::Wis, "Sailboat").unwrap(), 4);
+        assert!(ch.skill_table().unwrap().iter().all( |row| Skill::skills().contains(&row.skill) ));
+    }
+    #[test]
+    fn test_any_artisans_tools() {
+        let data = data_store_with_tools();
+        let mut ch = Character::new(&data);
+        assert!(ch.open_tool_choices().is_empty());
+        ch.set_background(data.get_background("Sailor").unwrap());
+        assert_eq!(ch.tool_level("Sailboat").unwrap(), SkillLevel::Proficient);
+        assert_eq!(ch.open_tool_choices(), vec![ToolCategory::ArtisanTools]);
+        assert_eq!(data.tools_in_category(ToolCategory::ArtisanTools).len(), 2);
+        assert!(ch.choose_tool(data.get_tool("Thieves' Tools").unwrap()).is_err());
+        ch.choose_tool(data.get_tool("Smith's Tools").unwrap()).unwrap();
+        assert_eq!(ch.tool_level("Smith's Tools").unwrap(), SkillLevel::Proficient);
+        assert_eq!(ch.tool_level("Weaver's Tools").unwrap(), SkillLevel::None);
+        assert!(ch.choose_tool(data.get_tool("Weaver's Tools").unwrap()).is_err());
+    }
+    #[test]
+    fn test_highest_tool_level_counts() {
+        let data = data_store_with_tools();
+        let mut ch = Character::new(&data);
+        ch.set_background(data.get_background("Sailor").unwrap());
+        ch.set_tool_level(data.get_tool("Sailboat").unwrap(), SkillLevel::HalfProficient(Rounding::Down));
+        assert_eq!(ch.tool_level("Sailboat").unwrap(), SkillLevel::Proficient);
+        ch.set_tool_level(data.get_tool("Sailboat").unwrap(), SkillLevel::Expert);
+        assert_eq!(ch.tool_level("Sailboat").unwrap(), SkillLevel::Expert);
+    }
+
+    fn data_store_with_tools() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_backgrounds(add_tools(data));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_skill_sources {
+    use super::*;
+    #[test]
+    fn test_highest_skill_level_wins() {
+        let data = data_store_with_races_classes_and_feats();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        ch.set_class(data.get_class("Bard").unwrap()).unwrap();
+        assert_eq!(ch.skill_level(&Skill::Persuasion).unwrap(), SkillLevel::Proficient);
+        ch.set_level(3).unwrap();
+        assert_eq!(ch.skill_level(&Skill::Persuasion).unwrap(), SkillLevel::Expert);
+        assert_eq!(ch.skill_sources(&Skill::Persuasion).unwrap(), vec![
+            (ProficiencySource::Race("Angel".to_owned()), SkillLevel::Proficient),
+            (ProficiencySource::Feature("Jack of All Trades".to_owned()), SkillLevel::HalfProficient(Rounding::Down)),
+            (ProficiencySource::Feature("Expertise".to_owned()), SkillLevel::Expert),
+        ]);
+        ch.set_skill_level(&Skill::Persuasion, SkillLevel::Proficient);
+        assert_eq!(ch.skill_level(&Skill::Persuasion).unwrap(), SkillLevel::Expert);
+    }
+    #[test]
+    fn test_feat_proficiency_beats_half_proficiency() {
+        let data = data_store_with_races_classes_and_feats();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Bard").unwrap()).unwrap();
+        ch.set_level(2).unwrap();
+        ch.set_ability(&Ability::Int, 14);
+        assert_eq!(ch.skill_level(&Skill::History).unwrap(), SkillLevel::HalfProficient(Rounding::Down));
+        ch.learn_feat(data.get_feat("Even Smarter").unwrap()).unwrap();
+        assert_eq!(ch.skill_level(&Skill::History).unwrap(), SkillLevel::Proficient);
+        assert!(ch.skill_sources(&Skill::History).unwrap().contains(&(ProficiencySource::Feat("Even Smarter".to_owned()), SkillLevel::Proficient)));
+    }
+
+    fn data_store_with_races_classes_and_feats() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_feats(add_classes(add_races(data)));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_saves {
+    use super::*;
+    #[test]
+    fn test_save_proficiencies_from_feats() {
+        let data = data_store_with_everything_affecting_saves();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Thief").unwrap()).unwrap();
+        assert_eq!(ch.save_proficiencies(&Ability::Dex), vec![ProficiencySource::Class("Thief".to_owned())]);
+        assert_eq!(ch.saving_throw(&Ability::Con), 0);
+        ch.learn_feat(data.get_feat("Resilient").unwrap()).unwrap();
+        assert_eq!(ch.save_proficiencies(&Ability::Con), vec![ProficiencySource::Feat("Resilient".to_owned())]);
+        assert_eq!(ch.saving_throw(&Ability::Con), 2);
+    }
+    #[test]
+    fn test_save_bonuses() {
+        let data = data_store_with_everything_affecting_saves();
+        let mut ch = Character::new(&data);
+        let cloak = ch.add_item(ItemKind::MagicItem, "Cloak of Protection", 1).unwrap();
+        ch.equip(cloak).unwrap();
+        assert_eq!(ch.saving_throw(&Ability::Wis), 0);
+        ch.attune(cloak).unwrap();
+        assert_eq!(ch.saving_throw(&Ability::Wis), 1);
+        assert_eq!(ch.armor_class().unwrap(), 11);
+        ch.add_condition(data.get_condition("Blessed").unwrap(), Duration::Rounds(10)).unwrap();
+        assert_eq!(ch.saving_throw(&Ability::Wis), 2);
+    }
+    #[test]
+    fn test_save_advantage_against_threats() {
+        let data = data_store_with_everything_affecting_saves();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Demon").unwrap()).unwrap();
+        assert_eq!(ch.save_advantages(&Ability::Con), vec![Some("poison".to_owned())]);
+        assert!(ch.save_advantages(&Ability::Wis).is_empty());
+        assert_eq!(ch.roll_mode(&RollContext::SaveAgainst(Ability::Con, "poison")).unwrap(), RollMode::Advantage);
+        assert_eq!(ch.roll_mode(&RollContext::SaveAgainst(Ability::Con, "disease")).unwrap(), RollMode::Normal);
+        assert_eq!(ch.roll_mode(&RollContext::Save(Ability::Con)).unwrap(), RollMode::Normal);
+    }
+    #[test]
+    fn test_saves_table() {
+        let data = data_store_with_everything_affecting_saves();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Demon").unwrap()).unwrap();
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        let table = ch.saves_table();
+        assert_eq!(table.len(), 6);
+        assert_eq!(table[2], SaveSummary {
+            ability : Ability::Con,
+            proficiencies : vec![ProficiencySource::Class("Warrior".to_owned())],
+            modifier : 3,
+            advantages : vec![Some("poison".to_owned())],
+        });
+    }
+
+    fn data_store_with_everything_affecting_saves() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_conditions(add_magic_items(add_equipment(add_feats(add_classes(add_races(data))))));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_racial_traits {
+    use super::*;
+    #[test]
+    fn test_traits_of_race_and_subrace() {
+        let data = data_store_with_racial_traits();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Halfbreed").unwrap()).unwrap();
+        assert_eq!(ch.darkvision(), Some(60));
+        assert!(ch.resistances().is_empty());
+        ch.set_subrace(data.get_race("Halfbreed").unwrap().get_subrace("Half-Demon").unwrap()).unwrap();
+        assert_eq!(ch.darkvision(), Some(120));
+        assert_eq!(ch.resistances(), vec![DamageType::Fire]);
+        assert_eq!(ch.natural_weapons()[0].damage_die, Die::D6);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        assert_eq!(ch.darkvision(), None);
+        assert!(ch.natural_weapons().is_empty());
+    }
+    #[test]
+    fn test_condition_immunity() {
+        let data = data_store_with_racial_traits();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Halfbreed").unwrap()).unwrap();
+        ch.set_subrace(data.get_race("Halfbreed").unwrap().get_subrace("Half-Demon").unwrap()).unwrap();
+        assert_eq!(ch.condition_immunities(), vec!["Poisoned"]);
+        assert!(ch.add_condition(data.get_condition("Poisoned").unwrap(), Duration::Indefinite).is_err());
+        assert!(!ch.has_condition("Poisoned"));
+        ch.add_condition(data.get_condition("Cursed").unwrap(), Duration::Indefinite).unwrap();
+        assert!(ch.has_condition("Cursed"));
+    }
+    #[test]
+    fn test_innate_spells_per_day() {
+        let data = data_store_with_racial_traits();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Halfbreed").unwrap()).unwrap();
+        ch.set_subrace(data.get_race("Halfbreed").unwrap().get_subrace("Half-Demon").unwrap()).unwrap();
+        assert!(ch.innate_spells().is_empty());
+        assert!(ch.cast_innate_spell("Magic Boot").is_err());
+        ch.set_level(3).unwrap();
+        assert_eq!(ch.innate_spells()[0].1, 1);
+        ch.cast_innate_spell("Magic Boot").unwrap();
+        assert_eq!(ch.innate_spells()[0].1, 0);
+        assert_eq!(ch.cast_innate_spell("Magic Boot"), Err(Error::NoUsesLeft("Magic Boot".to_owned())));
+        assert_eq!(ch.cast_innate_spell("Fireball"), Err(Error::missing(DataKind::Spell, "Fireball")));
+        ch.long_rest();
+        assert_eq!(ch.innate_spells()[0].1, 1);
+    }
+
+    fn data_store_with_racial_traits() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_conditions(add_spells(add_races(add_race_with_subraces(data))));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_movement {
+    use super::*;
+    #[test]
+    fn test_racial_speeds() {
+        let data = data_store_with_everything_affecting_speed();
+        let mut ch = Character::new(&data);
+        assert_eq!(ch.speeds(), Err(Error::NotChosen(DataKind::Race)));
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        let speeds = ch.speeds().unwrap();
+        assert_eq!(speeds.get(&MovementMode::Walk), 40);
+        assert_eq!(speeds.get(&MovementMode::Fly), 60);
+        assert_eq!(speeds.get(&MovementMode::Swim), 0);
+        assert!(speeds.hover);
+    }
+    #[test]
+    fn test_speeds_from_features_and_feats() {
+        let data = data_store_with_everything_affecting_speed();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Demon").unwrap()).unwrap();
+        ch.set_class(data.get_class("Thief").unwrap()).unwrap();
+        assert_eq!(ch.speeds().unwrap().climb, 30);
+        ch.learn_feat(data.get_feat("Mobile").unwrap()).unwrap();
+        assert_eq!(ch.speeds().unwrap(), Speeds { walk : 40, climb : 40, ..Speeds::default() });
+    }
+    #[test]
+    fn test_armor_strength_requirement() {
+        let data = data_store_with_everything_affecting_speed();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        let armor = ch.add_item(ItemKind::Armor, "Power Armor", 1).unwrap();
+        ch.equip(armor).unwrap();
+        assert_eq!(ch.speeds().unwrap(), Speeds { walk : 30, fly : 50, hover : true, ..Speeds::default() });
+        ch.set_ability(&Ability::Str, 15);
+        assert_eq!(ch.speed().unwrap(), 40);
+    }
+    #[test]
+    fn test_encumbrance_and_conditions_slow_all_speeds() {
+        let data = data_store_with_everything_affecting_speed();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        ch.set_encumbrance_rules(EncumbranceRules::Variant);
+        ch.add_item(ItemKind::Armor, "Power Armor", 1).unwrap();
+        assert_eq!(ch.speeds().unwrap().fly, 50);
+        ch.add_condition(data.get_condition("Restrained").unwrap(), Duration::Indefinite).unwrap();
+        let speeds = ch.speeds().unwrap();
+        assert_eq!(speeds.walk, 0);
+        assert_eq!(speeds.fly, 0);
+        assert!(speeds.hover);
+    }
+
+    fn data_store_with_everything_affecting_speed() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_conditions(add_equipment(add_feats(add_classes(add_races(data)))));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_damage {
+    use super::*;
+    #[test]
+    fn test_damage_defenses_from_all_sources() {
+        let data = data_store_with_everything_affecting_damage();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Halfbreed").unwrap()).unwrap();
+        ch.set_subrace(data.get_race("Halfbreed").unwrap().get_subrace("Half-Demon").unwrap()).unwrap();
+        ch.set_class(data.get_class("Hexer").unwrap()).unwrap();
+        assert_eq!(ch.resistances(), vec![DamageType::Fire, DamageType::Necrotic]);
+        assert_eq!(ch.vulnerabilities(), vec![DamageType::Radiant]);
+        ch.add_condition(data.get_condition("Petrified").unwrap(), Duration::Indefinite).unwrap();
+        assert_eq!(ch.resistances(), vec![DamageType::Fire, DamageType::Necrotic, DamageType::Slashing]);
+        assert_eq!(ch.immunities(), vec![DamageType::Poison]);
+    }
+    #[test]
+    fn test_resistance_from_attuned_item() {
+        let data = data_store_with_everything_affecting_damage();
+        let mut ch = Character::new(&data);
+        let ring = ch.add_item(ItemKind::MagicItem, "Ring of Fire Resistance", 1).unwrap();
+        ch.equip(ring).unwrap();
+        assert_eq!(ch.modified_damage(9, &DamageType::Fire), 9);
+        ch.attune(ring).unwrap();
+        assert_eq!(ch.modified_damage(9, &DamageType::Fire), 4);
+        assert_eq!(ch.modified_damage(9, &DamageType::Cold), 9);
+    }
+    #[test]
+    fn test_take_damage_and_heal() {
+        let data = data_store_with_everything_affecting_damage();
+        let mut ch = Character::new(&data);
+        assert_eq!(ch.take_damage(5, &DamageType::Fire), Err(Error::NotChosen(DataKind::Class)));
+        ch.set_class(data.get_class("Hexer").unwrap()).unwrap();
+        assert_eq!(ch.hit_points().unwrap(), 8);
+        assert_eq!(ch.take_damage(6, &DamageType::Necrotic).unwrap(), 3);
+        assert_eq!(ch.hit_points().unwrap(), 5);
+        assert_eq!(ch.take_damage(3, &DamageType::Radiant).unwrap(), 6);
+        assert_eq!(ch.hit_points().unwrap(), 0);
+        ch.heal(3);
+        assert_eq!(ch.hit_points().unwrap(), 3);
+        ch.heal(20);
+        assert_eq!(ch.hit_points().unwrap(), 8);
+    }
+    #[test]
+    fn test_temporary_hit_points() {
+        let data = data_store_with_everything_affecting_damage();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Hexer").unwrap()).unwrap();
+        ch.gain_temporary_hit_points(5);
+        ch.gain_temporary_hit_points(3);
+        assert_eq!(ch.temporary_hit_points(), 5);
+        ch.take_damage(7, &DamageType::Slashing).unwrap();
+        assert_eq!(ch.temporary_hit_points(), 0);
+        assert_eq!(ch.hit_points().unwrap(), 6);
+        ch.gain_temporary_hit_points(4);
+        ch.long_rest();
+        assert_eq!(ch.temporary_hit_points(), 0);
+        assert_eq!(ch.hit_points().unwrap(), 8);
+    }
+    #[test]
+    fn test_lost_hit_points_survive_undo() {
+        let data = data_store_with_everything_affecting_damage();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        history.apply(&mut ch, Edit::SetClass("Hexer".to_owned())).unwrap();
+        history.apply(&mut ch, Edit::SetName("Hexy".to_owned())).unwrap();
+        ch.take_damage(2, &DamageType::Fire).unwrap();
+        history.undo(&mut ch).unwrap();
+        assert_eq!(ch.hit_points().unwrap(), 6);
+    }
+
+    fn data_store_with_everything_affecting_damage() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_conditions(add_magic_items(add_equipment(add_classes(add_race_with_subraces(data)))));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_senses {
+    use super::*;
+    #[test]
+    fn test_alert_feat() {
+        let data = data_store_with_everything_affecting_senses();
+        let mut ch = Character::new(&data);
+        ch.set_ability(&Ability::Dex, 14);
+        assert_eq!(ch.initiative(), 2);
+        assert!(ch.can_be_surprised());
+        ch.learn_feat(data.get_feat("Alert").unwrap()).unwrap();
+        assert_eq!(ch.initiative(), 7);
+        assert!(!ch.can_be_surprised());
+    }
+    #[test]
+    fn test_passive_scores() {
+        let data = data_store_with_everything_affecting_senses();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        ch.set_skill_level(&Skill::Perception, SkillLevel::Proficient);
+        assert_eq!(ch.passive_score(&Skill::Perception).unwrap(), 13);
+        assert_eq!(ch.passive_score(&Skill::Stealth).unwrap(), 10);
+        ch.learn_feat(data.get_feat("Sneaky").unwrap()).unwrap();
+        assert_eq!(ch.passive_score(&Skill::Stealth).unwrap(), 15);
+    }
+    #[test]
+    fn test_senses() {
+        let data = data_store_with_everything_affecting_senses();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Halfbreed").unwrap()).unwrap();
+        ch.set_ability(&Ability::Int, 14);
+        assert_eq!(ch.senses().unwrap(), Senses {
+            darkvision : Some(60),
+            passive_perception : 10,
+            passive_investigation : 12,
+            passive_insight : 10,
+        });
+    }
+
+    fn data_store_with_everything_affecting_senses() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_feats(add_races(add_race_with_subraces(data)));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_attacks {
+    use super::*;
+    #[test]
+    fn test_versatile_weapon_attacks() {
+        let data = data_store_with_everything_affecting_attacks();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        ch.set_ability(&Ability::Str, 16);
+        let sword = ch.add_item(ItemKind::MagicItem, "Bloodsword +2", 1).unwrap();
+        ch.equip(sword).unwrap();
+        let attacks = ch.attacks().unwrap();
+        assert_eq!(attacks.len(), 3);
+        assert_eq!(attacks[0], Attack {
+            name : "Bloodsword +2".to_owned(),
+            to_hit : 7,
+            damage_dice : 1,
+            damage_die : Die::D8,
+            damage_modifier : 5,
+            damage_type : DamageType::Slashing,
+            range : 5,
+            notes : Vec::new(),
+        });
+        assert_eq!(attacks[1].damage_die, Die::D10);
+        assert_eq!(attacks[1].notes, vec![AttackNote::TwoHanded]);
+        assert_eq!(attacks[2].name, "Unarmed Strike");
+        assert_eq!(attacks[2].to_hit, 5);
+        assert_eq!(attacks[2].damage_modifier, 4);
+        let shield = ch.add_item(ItemKind::Armor, "Tower Shield", 1).unwrap();
+        ch.equip(shield).unwrap();
+        assert_eq!(ch.attacks().unwrap().len(), 2);
+    }
+    #[test]
+    fn test_mundane_weapon_attack_gets_no_magic_bonus() {
+        let data = data_store_with_everything_affecting_attacks();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Warrior").unwrap()).unwrap();
+        let magic_sword = ch.add_item(ItemKind::MagicItem, "Bloodsword +2", 1).unwrap();
+        let mundane_sword = ch.add_item(ItemKind::Weapon, "Bloodsword", 1).unwrap();
+        ch.equip(magic_sword).unwrap();
+        ch.equip(mundane_sword).unwrap();
+        let attacks = ch.attacks().unwrap();
+        assert_eq!((attacks[0].to_hit, attacks[0].damage_modifier), (4, 2));
+        assert_eq!(attacks[1].name, "Bloodsword");
+        assert_eq!((attacks[1].to_hit, attacks[1].damage_modifier), (2, 0));
+    }
+    #[test]
+    fn test_two_weapon_fighting() {
+        let data = data_store_with_everything_affecting_attacks();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Thief").unwrap()).unwrap();
+        ch.set_ability(&Ability::Dex, 14);
+        let dagger = ch.add_item(ItemKind::Weapon, "Dagger", 1).unwrap();
+        ch.equip(dagger).unwrap();
+        let other_dagger = ch.add_item(ItemKind::Weapon, "Dagger", 1).unwrap();
+        ch.equip(other_dagger).unwrap();
+        let attacks = ch.attacks().unwrap();
+        assert_eq!(attacks.len(), 4);
+        assert_eq!(attacks[0].to_hit, 4);
+        assert_eq!(attacks[0].damage_modifier, 2);
+        assert_eq!(attacks[0].notes, vec![AttackNote::Finesse, AttackNote::Thrown]);
+        assert_eq!(attacks[2].damage_modifier, 0);
+        assert_eq!(attacks[2].notes, vec![AttackNote::Finesse, AttackNote::Thrown, AttackNote::OffHand]);
+    }
+    #[test]
+    fn test_unproficient_weapon_attack() {
+        let data = data_store_with_everything_affecting_attacks();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Mage").unwrap()).unwrap();
+        let bow = ch.add_item(ItemKind::Weapon, "Beau's Bow", 1).unwrap();
+        ch.equip(bow).unwrap();
+        let attacks = ch.attacks().unwrap();
+        assert_eq!(attacks[0].to_hit, 0);
+        assert_eq!(attacks[0].range, 60);
+        assert_eq!(attacks[0].notes, vec![AttackNote::NotProficient]);
+    }
+    #[test]
+    fn test_natural_weapon_and_cantrip_attacks() {
+        let data = data_store_with_everything_affecting_attacks();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Halfbreed").unwrap()).unwrap();
+        ch.set_subrace(data.get_race("Halfbreed").unwrap().get_subrace("Half-Demon").unwrap()).unwrap();
+        ch.set_class(data.get_class("Mage").unwrap()).unwrap();
+        ch.set_ability(&Ability::Int, 16);
+        ch.learn_spell(data.get_spell("Spark").unwrap(), Ability::Int);
+        ch.learn_spell(data.get_spell("Magic Boot").unwrap(), Ability::Int);
+        let attacks = ch.attacks().unwrap();
+        assert_eq!(attacks.len(), 3);
+        assert_eq!(attacks[1].name, "Claws");
+        assert_eq!(attacks[1].damage_die, Die::D6);
+        assert_eq!(attacks[2], Attack {
+            name : "Spark".to_owned(),
+            to_hit : 5,
+            damage_dice : 1,
+            damage_die : Die::D10,
+            damage_modifier : 0,
+            damage_type : DamageType::Lightning,
+            range : 120,
+            notes : vec![AttackNote::Cantrip],
+        });
+        ch.set_level(5).unwrap();
+        let attacks = ch.attacks().unwrap();
+        assert_eq!(attacks[2].damage_dice, 2);
+        assert_eq!(attacks[2].to_hit, 6);
+    }
+
+    fn data_store_with_everything_affecting_attacks() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_spells(add_magic_items(add_equipment(add_classes(add_race_with_subraces(data)))));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_race_and_equipment_data_dependent_features {
+    use super::*;
+    #[test]
+    fn test_carrying_capacity_depends_on_size() {
+        let data = data_store_with_races_and_equipment();
+        let mut ch = Character::new(&data);
+        assert_eq!(ch.carrying_capacity(), Err(Error::NotChosen(DataKind::Race)));
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        assert_eq!(ch.carrying_capacity().unwrap(), 150 * POUND);
+        assert_eq!(ch.push_drag_lift().unwrap(), 300 * POUND);
+        ch.set_race(data.get_race("Demon").unwrap()).unwrap();
+        assert_eq!(ch.carrying_capacity().unwrap(), 300 * POUND);
+    }
+    #[test]
+    fn test_standard_encumbrance() {
+        let data = data_store_with_races_and_equipment();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        let power_armor = ch.add_item(ItemKind::Armor, "Power Armor", 2).unwrap();
+        assert_eq!(ch.encumbrance().unwrap(), Encumbrance::Unencumbered);
+        assert_eq!(ch.speed().unwrap(), 40);
+        ch.add_item(ItemKind::Armor, "Power Armor", 1).unwrap();
+        assert_eq!(ch.encumbrance().unwrap(), Encumbrance::OverCapacity);
+        assert_eq!(ch.speed().unwrap(), 5);
+        ch.add_item(ItemKind::Armor, "Power Armor", 2).unwrap();
+        assert_eq!(ch.speed().unwrap(), 0);
+        ch.remove_item(power_armor, 5).unwrap();
+        assert_eq!(ch.speed().unwrap(), 40);
+    }
+    #[test]
+    fn test_variant_encumbrance() {
+        let data = data_store_with_races_and_equipment();
+        let mut ch = Character::new(&data);
+        ch.set_race(data.get_race("Angel").unwrap()).unwrap();
+        ch.set_encumbrance_rules(EncumbranceRules::Variant);
+        ch.add_item(ItemKind::Armor, "Power Armor", 1).unwrap();
+        assert_eq!(ch.encumbrance().unwrap(), Encumbrance::Encumbered);
+        assert_eq!(ch.speed().unwrap(), 30);
+        ch.add_item(ItemKind::Armor, "Power Armor", 1).unwrap();
+        assert_eq!(ch.encumbrance().unwrap(), Encumbrance::HeavilyEncumbered);
+        assert_eq!(ch.speed().unwrap(), 20);
+    }
+
+    fn data_store_with_races_and_equipment() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_equipment(add_races(data));
+        data
+    }
+}
+
 #[cfg(test)]
 mod test_race_subrace_and_equipment_data_dependent_features {
     use super::*;
@@ -192,22 +1350,32 @@ mod test_subrace_dependent_features {
         ch.set_subrace(half_angel).unwrap();
         assert_eq!(*ch.ability(&Ability::Wis), 11);
         assert!(ch.speaks("Angelic"));
-        assert_eq!(*ch.skill_level(&Skill::Persuasion), SkillLevel::Proficient);
+        assert_eq!(ch.skill_level(&Skill::Persuasion).unwrap(), SkillLevel::Proficient);
     }
     #[test]
     fn test_setting_second_subrace_undoes_effects_of_first() {
         let data = data_store_with_subrace();
         let mut ch = Character::new(&data);
         let halfbreed = data.get_race("Halfbreed").unwrap();
-        ch.set_race(halfbreed).unwrap();
+        ch.set_race(halfbreed).unwrap();
+        let half_angel = halfbreed.get_subrace("Half-Angel").unwrap();
+        ch.set_subrace(half_angel).unwrap();
+        let half_demon = halfbreed.get_subrace("Half-Demon").unwrap();
+        ch.set_subrace(half_demon).unwrap();        
+        assert_eq!(*ch.ability(&Ability::Wis), 10);
+        assert_eq!(*ch.ability(&Ability::Con), 11);
+        assert_eq!(ch.skill_level(&Skill::Persuasion).unwrap(), SkillLevel::None);
+        assert_eq!(ch.skill_level(&Skill::Intimidation).unwrap(), SkillLevel::Proficient);
+    }
+
+    #[test]
+    fn test_subrace_must_belong_to_race() {
+        let data = data_store_with_subrace();
+        let mut ch = Character::new(&data);
+        let halfbreed = data.get_race("Halfbreed").unwrap();
         let half_angel = halfbreed.get_subrace("Half-Angel").unwrap();
-        ch.set_subrace(half_angel).unwrap();
-        let half_demon = halfbreed.get_subrace("Half-Demon").unwrap();
-        ch.set_subrace(half_demon).unwrap();        
-        assert_eq!(*ch.ability(&Ability::Wis), 10);
-        assert_eq!(*ch.ability(&Ability::Con), 11);
-        assert_eq!(*ch.skill_level(&Skill::Persuasion), SkillLevel::None);
-        assert_eq!(*ch.skill_level(&Skill::Intimidation), SkillLevel::Proficient);
+        assert_eq!(ch.set_subrace(half_angel), Err(Error::NotChosen(DataKind::Race)));
+        assert_eq!(ch.size(), Err(Error::NotChosen(DataKind::Race)));
     }
 
     fn data_store_with_subrace() -> Datastore {
@@ -221,14 +1389,30 @@ mod test_subrace_dependent_features {
 mod test_class_dependent_features{
     use super::*;
     #[test]
+    fn test_jack_of_all_trades() {
+        let data = data_store_with_classes();
+        let mut ch = Character::new(&data);
+        ch.set_ability(&Ability::Dex, 14);
+        ch.set_class(data.get_class("Bard").unwrap()).unwrap();
+        assert_eq!(ch.initiative(), 2);
+        assert_eq!(ch.skill_level(&Skill::Perception).unwrap(), SkillLevel::None);
+        ch.set_level(5).unwrap();
+        assert_eq!(ch.initiative(), 3);
+        assert_eq!(ch.ability_check_mod(&Ability::Str), 1);
+        assert_eq!(ch.skill_level(&Skill::Perception).unwrap(), SkillLevel::HalfProficient(Rounding::Down));
+        assert_eq!(ch.skill_mod_default(&Skill::Perception).unwrap(), 1);
+        assert_eq!(ch.skill_mod_default(&Skill::Performance).unwrap(), 3);
+        assert_eq!(ch.proficiency_modifier(&SkillLevel::HalfProficient(Rounding::Up)), 2);
+    }
+    #[test]
     fn test_setting_character_class() {
         let data = data_store_with_classes();
         let mut ch = Character::new(&data);
         let warrior = data.get_class("Warrior").unwrap();
-        ch.set_class(warrior);
+        ch.set_class(warrior).unwrap();
         assert_eq!(*ch.hit_die().unwrap(), Die::D10);
         assert_eq!(ch.saving_throw(&Ability::Str), 2);
-        assert_eq!(*ch.skill_level(&Skill::Athletics), SkillLevel::Proficient);
+        assert_eq!(ch.skill_level(&Skill::Athletics).unwrap(), SkillLevel::Proficient);
     }
     #[test]
     fn test_setting_different_class_undoes_effects_of_first_class() {
@@ -236,29 +1420,64 @@ mod test_class_dependent_features{
         let mut ch = Character::new(&data);
         let warrior = data.get_class("Warrior").unwrap();
         let thief = data.get_class("Thief").unwrap();
-        ch.set_class(warrior);
-        ch.set_class(thief);
+        ch.set_class(warrior).unwrap();
+        ch.set_class(thief).unwrap();
         assert_eq!(*ch.hit_die().unwrap(), Die::D6);
         assert_eq!(ch.saving_throw(&Ability::Str), 0);
         assert_eq!(ch.saving_throw(&Ability::Dex), 2);
-        assert_eq!(*ch.skill_level(&Skill::Athletics), SkillLevel::None);
-        assert_eq!(*ch.skill_level(&Skill::Acrobatics), SkillLevel::Proficient);
+        assert_eq!(ch.skill_level(&Skill::Athletics).unwrap(), SkillLevel::None);
+        assert_eq!(ch.skill_level(&Skill::Acrobatics).unwrap(), SkillLevel::Proficient);
     }
     #[test]
     fn test_spellcasting() {
         let data = data_store_with_classes();
         let mut ch = Character::new(&data);
         let mage = data.get_class("Mage").unwrap();
-        ch.set_class(mage);
+        ch.set_class(mage).unwrap();
         assert_eq!(
-            ch.spell_slots(),
+            ch.spell_slots().unwrap(),
             SpellLevel::slots(2,0,0,0,0,0,0,0,0)
         );
-        ch.set_level(10);
+        ch.set_level(10).unwrap();
         assert_eq!(
-            ch.spell_slots(),
+            ch.spell_slots().unwrap(),
             SpellLevel::slots(4,3,3,3,2,0,0,0,0)
-        )
+        );
+        ch.set_level(21).unwrap();
+        assert_eq!(ch.spell_slots(), Err(Error::InvalidLevel(21)));
+    }
+    #[test]
+    fn test_custom_spell_progression() {
+        let mut data = data_store_with_classes();
+        data.add_spell_progression(
+            SpellCaster::Custom("Runic".to_owned()),
+            SpellProgression::from_table(SlotRecovery::LongRest, None, &[[1,0,0,0,0,0,0,0,0], [1,1,0,0,0,0,0,0,0]])
+        );
+        data.add_class(
+            Class {
+                name : "Runecarver".to_owned(),
+                long_text : "Magic in stone".to_owned(),
+                hit_die : Die::D8,
+                saving_throws : vec![Ability::Con, Ability::Int],
+                combat_proficiencies : Vec::new(),
+                skill_proficiencies : Vec::new(),
+                tool_proficiencies : Vec::new(),
+                spell_caster : SpellCaster::Custom("Runic".to_owned()),
+                spellcasting_ability : None,
+                spells_learned : Vec::new(),
+                features : Vec::new(),
+                asi_levels : Vec::new(),
+                subclass_level : 0,
+                subclasses : HashMap::new(),
+                starting_equipment : StartingEquipment::default(),
+            }
+        );
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Runecarver").unwrap()).unwrap();
+        ch.set_level(2).unwrap();
+        assert_eq!(ch.spell_slots().unwrap(), SpellLevel::slots(1,1,0,0,0,0,0,0,0));
+        ch.set_level(3).unwrap();
+        assert!(ch.spell_slots().is_err());
     }
 
     fn data_store_with_classes() -> Datastore {
@@ -268,7 +1487,7 @@ mod test_class_dependent_features{
     }
 }
 
-use crate::datastore::SpellCaster;
+use crate::datastore::{ SpellCaster, SpellProgression, SlotRecovery, Subclass, ClassFeature };
 #[cfg(test)]
 mod test_class_and_equipment_dependent_features{
     use super::*;
@@ -280,9 +1499,9 @@ mod test_class_and_equipment_dependent_features{
         let thief = data.get_class("Thief").unwrap();
         let sword = data.get_weapon("Bloodsword").unwrap();
         let bow = data.get_weapon("Beau's Bow").unwrap();
-        ch.set_class(warrior);
+        ch.set_class(warrior).unwrap();
         assert_eq!(ch.get_attack_mod(sword), 2);
-        ch.set_class(thief);
+        ch.set_class(thief).unwrap();
         assert_eq!(ch.get_attack_mod(sword), 0);
         assert_eq!(ch.get_attack_mod(bow), 2);
     }
@@ -318,17 +1537,28 @@ mod test_feat_data_dependent_features {
         let data = data_store_with_feats();
         let mut ch = Character::new(&data);
         let even_smarter = data.get_feat("Even Smarter").unwrap();
-        match ch.learn_feat(even_smarter) {
-            Ok(_) => panic!("Character learned feat without meeting the prerequisites"),
-            Err(_) => (),
-        };
+        assert_eq!(
+            ch.learn_feat(even_smarter),
+            Err(Error::PrerequisitesNotMet { feat : "Even Smarter".to_owned() })
+        );
         ch.set_ability(&Ability::Int,14);
         match ch.learn_feat(even_smarter) {
             Ok(_) => (),
             Err(_) => panic!("Character didn't learn feat after meeting the prerequisites"),
         };
         assert_eq!(*ch.ability(&Ability::Int), 16);
-        assert_eq!(*ch.skill_level(&Skill::History), SkillLevel::Proficient);
+        assert_eq!(ch.skill_level(&Skill::History).unwrap(), SkillLevel::Proficient);
+    }
+    #[test]
+    fn test_feat_missing_from_datastore() {
+        let data = data_store_with_feats();
+        let other_data = Datastore::new();
+        let mut ch = Character::new(&other_data);
+        ch.learn_feat(data.get_feat("Strong").unwrap()).unwrap();
+        assert_eq!(
+            ch.skill_level(&Skill::History),
+            Err(Error::missing(DataKind::Feat, "Strong"))
+        );
     }
     fn data_store_with_feats() -> Datastore {
         let mut data = Datastore::new();
@@ -369,6 +1599,364 @@ mod test_spell_data_dependent_features {
     }
 }
 
+#[cfg(test)]
+mod test_spell_and_class_data_dependent_features {
+    use super::*;
+    #[test]
+    fn test_casting_spells_uses_up_slots() {
+        let data = data_store_with_spells_and_classes();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Mage").unwrap()).unwrap();
+        let magic_boot = data.get_spell("Magic Boot").unwrap();
+        ch.learn_spell(magic_boot, Ability::Int);
+        ch.cast_spell(magic_boot, SpellLevel::First).unwrap();
+        assert_eq!(ch.remaining_spell_slots().unwrap()[&SpellLevel::First], 1);
+        ch.cast_spell(magic_boot, SpellLevel::First).unwrap();
+        assert_eq!(ch.cast_spell(magic_boot, SpellLevel::First), Err(Error::NoSpellSlot(SpellLevel::First)));
+        ch.short_rest();
+        assert!(ch.cast_spell(magic_boot, SpellLevel::First).is_err());
+        ch.long_rest();
+        assert_eq!(ch.remaining_spell_slots().unwrap()[&SpellLevel::First], 2);
+    }
+    #[test]
+    fn test_upcasting_spells() {
+        let data = data_store_with_spells_and_classes();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Mage").unwrap()).unwrap();
+        ch.set_level(3).unwrap();
+        let magic_boot = data.get_spell("Magic Boot").unwrap();
+        assert!(ch.cast_spell(magic_boot, SpellLevel::Second).is_err());
+        ch.learn_spell(magic_boot, Ability::Int);
+        ch.cast_spell(magic_boot, SpellLevel::Second).unwrap();
+        assert_eq!(ch.remaining_spell_slots().unwrap()[&SpellLevel::First], 4);
+        assert_eq!(ch.remaining_spell_slots().unwrap()[&SpellLevel::Second], 1);
+        assert!(ch.cast_spell(magic_boot, SpellLevel::Cantrip).is_err());
+    }
+    #[test]
+    fn test_cantrips_do_not_use_slots() {
+        let data = data_store_with_spells_and_classes();
+        let mut ch = Character::new(&data);
+        let spark = data.get_spell("Spark").unwrap();
+        ch.learn_spell(spark, Ability::Int);
+        ch.cast_spell(spark, SpellLevel::Cantrip).unwrap();
+        ch.cast_spell(spark, SpellLevel::Cantrip).unwrap();
+    }
+
+    #[test]
+    fn test_pact_slots_recover_on_short_rest() {
+        let data = data_store_with_spells_and_classes();
+        let mut ch = Character::new(&data);
+        ch.set_class(data.get_class("Hexer").unwrap()).unwrap();
+        ch.set_level(3).unwrap();
+        assert_eq!(ch.spell_slots().unwrap(), SpellLevel::slots(0,0,0,0,0,0,0,0,0));
+        assert_eq!(ch.short_rest_spell_slots().unwrap(), SpellLevel::slots(0,2,0,0,0,0,0,0,0));
+        let magic_boot = data.get_spell("Magic Boot").unwrap();
+        ch.learn_spell(magic_boot, Ability::Cha);
+        assert!(ch.cast_spell(magic_boot, SpellLevel::First).is_err());
+        ch.cast_spell(magic_boot, SpellLevel::Second).unwrap();
+        ch.cast_spell(magic_boot, SpellLevel::Second).unwrap();
+        assert!(ch.cast_spell(magic_boot, SpellLevel::Second).is_err());
+        ch.short_rest();
+        assert_eq!(ch.remaining_short_rest_spell_slots().unwrap()[&SpellLevel::Second], 2);
+    }
+
+    fn data_store_with_spells_and_classes() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_classes(add_spells(data));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_edit_history {
+    use super::*;
+    #[test]
+    fn test_undo_and_redo_edits() {
+        let data = data_store_with_races_and_feats();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        history.apply(&mut ch, Edit::SetRace("Angel".to_owned())).unwrap();
+        history.apply(&mut ch, Edit::LearnFeat("Strong".to_owned())).unwrap();
+        history.apply(&mut ch, Edit::SetRace("Demon".to_owned())).unwrap();
+        assert!(history.undo(&mut ch).unwrap());
+        assert_eq!(*ch.ability(&Ability::Wis), 12);
+        assert_eq!(*ch.ability(&Ability::Str), 12);
+        assert!(history.undo(&mut ch).unwrap());
+        assert_eq!(*ch.ability(&Ability::Str), 10);
+        assert!(history.redo(&mut ch).unwrap());
+        assert!(history.redo(&mut ch).unwrap());
+        assert!(!history.redo(&mut ch).unwrap());
+        assert!(ch.speaks("Demonic"));
+        assert_eq!(*ch.ability(&Ability::Str), 12);
+    }
+    #[test]
+    fn test_new_edit_discards_redo() {
+        let data = data_store_with_races_and_feats();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        history.apply(&mut ch, Edit::SetAbility(Ability::Int, 14)).unwrap();
+        history.undo(&mut ch).unwrap();
+        assert!(history.can_redo());
+        history.apply(&mut ch, Edit::SetName("Dude".to_owned())).unwrap();
+        assert!(!history.can_redo());
+        assert_eq!(*ch.ability(&Ability::Int), 10);
+    }
+    #[test]
+    fn test_failed_edits_are_not_recorded() {
+        let data = data_store_with_races_and_feats();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        assert_eq!(
+            history.apply(&mut ch, Edit::LearnFeat("Even Smarter".to_owned())),
+            Err(Error::PrerequisitesNotMet { feat : "Even Smarter".to_owned() })
+        );
+        assert_eq!(
+            history.apply(&mut ch, Edit::SetRace("Dwarf".to_owned())),
+            Err(Error::missing(DataKind::Race, "Dwarf"))
+        );
+        assert!(!history.can_undo());
+    }
+    #[test]
+    fn test_replaying_history() {
+        let data = data_store_with_races_and_feats();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        history.apply(&mut ch, Edit::SetAbility(Ability::Int, 14)).unwrap();
+        history.apply(&mut ch, Edit::LearnFeat("Even Smarter".to_owned())).unwrap();
+        history.apply(&mut ch, Edit::SetRace("Angel".to_owned())).unwrap();
+        let replayed = history.replay(&data).unwrap();
+        assert_eq!(*replayed.ability(&Ability::Int), 16);
+        assert_eq!(*replayed.ability(&Ability::Wis), 12);
+        assert_eq!(replayed.skill_level(&Skill::History).unwrap(), SkillLevel::Proficient);
+    }
+    #[test]
+    fn test_failed_redo_can_be_retried() {
+        let data = data_store_with_races_and_feats();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        history.apply(&mut ch, Edit::GainCurrency(Currency { gp : 10, ..Currency::default() })).unwrap();
+        history.apply(&mut ch, Edit::SpendCurrency(Currency { gp : 10, ..Currency::default() })).unwrap();
+        history.undo(&mut ch).unwrap();
+        ch.spend_currency(&Currency { gp : 5, ..Currency::default() }).unwrap();
+        assert!(history.redo(&mut ch).is_err());
+        assert!(history.can_redo());
+        assert_eq!(history.edits().len(), 1);
+        ch.gain_currency(&Currency { gp : 5, ..Currency::default() }).unwrap();
+        assert!(history.redo(&mut ch).unwrap());
+        assert_eq!(history.edits().len(), 2);
+    }
+    #[test]
+    fn test_play_state_survives_undo() {
+        let data = data_store_with_everything_used_in_play();
+        let mut ch = Character::new(&data);
+        let mut history = History::new();
+        history.apply(&mut ch, Edit::SetRace("Halfbreed".to_owned())).unwrap();
+        history.apply(&mut ch, Edit::SetSubrace("Half-Demon".to_owned())).unwrap();
+        history.apply(&mut ch, Edit::SetClass("Mage".to_owned())).unwrap();
+        history.apply(&mut ch, Edit::SetLevel(3)).unwrap();
+        history.apply(&mut ch, Edit::LearnSpell("Magic Boot".to_owned(), Ability::Int)).unwrap();
+        history.apply(&mut ch, Edit::SetName("Player".to_owned())).unwrap();
+        ch.cast_spell(data.get_spell("Magic Boot").unwrap(), SpellLevel::First).unwrap();
+        ch.cast_innate_spell("Magic Boot").unwrap();
+        ch.add_condition(data.get_condition("Cursed").unwrap(), Duration::UntilLongRest).unwrap();
+        ch.gain_exhaustion().unwrap();
+        ch.take_damage(2, &DamageType::Cold).unwrap();
+        ch.gain_temporary_hit_points(3);
+        let slots = ch.remaining_spell_slots().unwrap();
+        let hit_points = ch.hit_points().unwrap();
+        history.undo(&mut ch).unwrap();
+        assert_eq!(ch.remaining_spell_slots().unwrap(), slots);
+        assert_eq!(ch.innate_spells()[0].1, 0);
+        assert!(ch.has_condition("Cursed"));
+        assert_eq!(ch.exhaustion(), 1);
+        assert_eq!(ch.hit_points().unwrap(), hit_points);
+        assert_eq!(ch.temporary_hit_points(), 3);
+    }
+
+    fn data_store_with_races_and_feats() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_feats(add_races(data));
+        data
+    }
+    fn data_store_with_everything_used_in_play() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_conditions(add_spells(add_classes(add_race_with_subraces(data))));
+        data
+    }
+}
+
+#[cfg(test)]
+mod test_level_up {
+    use super::*;
+    #[test]
+    fn test_first_level_up() {
+        let data = data_store_with_classes_and_spells();
+        let mut ch = Character::new(&data);
+        let warrior = data.get_class("Warrior").unwrap();
+        let mut level_up = ch.level_up(warrior).unwrap();
+        assert_eq!(
+            level_up.decisions(),
+            &[Decision::Feature { name : "Fighting Style".to_owned(), options : vec!["Archery".to_owned(), "Defense".to_owned()] }]
+        );
+        assert!(level_up.resolve(0, Choice::Feature("Dancing".to_owned()), &data).is_err());
+        assert!(ch.apply_level_up(level_up.clone()).is_err());
+        level_up.resolve(0, Choice::Feature("Archery".to_owned()), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        assert_eq!(ch.class_levels(), vec![("Warrior", 1)]);
+        assert_eq!(ch.level(), 1);
+        assert_eq!(ch.max_hit_points().unwrap(), 10);
+        assert_eq!(ch.feature_choice("Fighting Style"), Some("Archery"));
+    }
+    #[test]
+    fn test_choosing_a_subclass() {
+        let data = data_store_with_classes_and_spells();
+        let mut ch = Character::new(&data);
+        let warrior = data.get_class("Warrior").unwrap();
+        let mut level_up = ch.level_up(warrior).unwrap();
+        level_up.resolve(0, Choice::Feature("Defense".to_owned()), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        let mut level_up = ch.level_up(warrior).unwrap();
+        assert_eq!(level_up.decisions(), &[Decision::HitPoints(Die::D10)]);
+        assert_eq!(level_up.features()[0].name, "Second Wind");
+        level_up.resolve(0, Choice::HitPoints(HitPointChoice::Rolled(3)), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        let mut level_up = ch.level_up(warrior).unwrap();
+        assert_eq!(level_up.decisions()[1], Decision::Subclass(vec!["Berserker".to_owned(), "Champion".to_owned()]));
+        level_up.resolve(0, Choice::HitPoints(HitPointChoice::Average), &data).unwrap();
+        level_up.resolve(1, Choice::Subclass("Champion".to_owned()), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        assert_eq!(ch.subclass("Warrior"), Some("Champion"));
+        assert_eq!(ch.max_hit_points().unwrap(), 10 + 3 + 6);
+        let features : Vec<&str> = ch.features().iter().map( |feature| feature.name.as_str() ).collect();
+        assert_eq!(features, vec!["Fighting Style", "Second Wind", "Improved Critical"]);
+        ch.level_down().unwrap();
+        assert_eq!(ch.subclass("Warrior"), None);
+        assert_eq!(ch.level(), 2);
+    }
+    #[test]
+    fn test_level_ups_are_kept_consistent() {
+        let data = data_store_with_classes_and_spells();
+        let mut ch = Character::new(&data);
+        let warrior = data.get_class("Warrior").unwrap();
+        ch.set_level(5).unwrap();
+        let mut level_up = ch.level_up(warrior).unwrap();
+        level_up.resolve(0, Choice::Feature("Defense".to_owned()), &data).unwrap();
+        assert!(ch.apply_level_up(level_up.clone()).is_err());
+        assert_eq!(ch.level(), 5);
+        ch.set_level(1).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        let mut level_up = ch.level_up(warrior).unwrap();
+        level_up.resolve(0, Choice::HitPoints(HitPointChoice::Rolled(6)), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        assert!(ch.set_level(1).is_err());
+        assert!(ch.set_class(data.get_class("Thief").unwrap()).is_err());
+        assert_eq!(ch.level(), 2);
+        assert_eq!(ch.class_levels(), vec![("Warrior", 2)]);
+        assert_eq!(ch.max_hit_points().unwrap(), 10 + 6);
+    }
+    #[test]
+    fn test_subclass_features_at_subclass_level() {
+        let data = data_store_with_classes_and_spells();
+        let mut ch = Character::new(&data);
+        let warrior = data.get_class("Warrior").unwrap();
+        ch.set_class(warrior).unwrap();
+        ch.set_level(2).unwrap();
+        let mut level_up = ch.level_up(warrior).unwrap();
+        assert!(level_up.features().is_empty());
+        level_up.resolve(0, Choice::HitPoints(HitPointChoice::Average), &data).unwrap();
+        level_up.resolve(1, Choice::Subclass("Champion".to_owned()), &data).unwrap();
+        assert_eq!(level_up.features()[0].name, "Improved Critical");
+        assert_eq!(level_up.decisions().len(), 2);
+        level_up.resolve(1, Choice::Subclass("Berserker".to_owned()), &data).unwrap();
+        assert_eq!(level_up.features().len(), 1);
+        assert_eq!(level_up.features()[0].name, "Path of Rage");
+        assert_eq!(level_up.decisions()[2], Decision::Feature {
+            name : "Path of Rage".to_owned(),
+            options : vec!["Frenzy".to_owned(), "Mindless Rage".to_owned()],
+        });
+        assert!(!level_up.is_resolved());
+        level_up.resolve(2, Choice::Feature("Frenzy".to_owned()), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        assert!(ch.features().iter().any( |feature| feature.name == "Path of Rage" ));
+        assert_eq!(ch.feature_choice("Path of Rage"), Some("Frenzy"));
+    }
+    #[test]
+    fn test_multiclass_level_up_and_down() {
+        let data = data_store_with_classes_and_spells();
+        let mut ch = Character::new(&data);
+        ch.set_ability(&Ability::Con, 14);
+        ch.set_class(data.get_class("Thief").unwrap()).unwrap();
+        let mut level_up = ch.level_up(data.get_class("Mage").unwrap()).unwrap();
+        assert_eq!(
+            level_up.decisions(),
+            &[Decision::HitPoints(Die::D4), Decision::Spells { count : 2, max_level : SpellLevel::First }]
+        );
+        level_up.resolve(0, Choice::HitPoints(HitPointChoice::Average), &data).unwrap();
+        assert!(level_up.resolve(1, Choice::Spells(vec!["Magic Boot".to_owned()]), &data).is_err());
+        level_up.resolve(1, Choice::Spells(vec!["Magic Boot".to_owned(), "Spark".to_owned()]), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        assert_eq!(ch.class_levels(), vec![("Thief", 1), ("Mage", 1)]);
+        assert_eq!(ch.spells().len(), 2);
+        assert_eq!(ch.spell_slots().unwrap(), SpellLevel::slots(2,0,0,0,0,0,0,0,0));
+        assert_eq!(ch.max_hit_points().unwrap(), 8 + 5);
+        ch.level_down().unwrap();
+        assert_eq!(ch.class_levels(), vec![("Thief", 1)]);
+        assert_eq!(ch.spells().len(), 0);
+        assert!(ch.level_down().is_err());
+    }
+
+    #[test]
+    fn test_ability_score_improvement() {
+        let data = data_store_with_classes_and_spells();
+        let mut ch = Character::new(&data);
+        let thief = data.get_class("Thief").unwrap();
+        ch.set_class(thief).unwrap();
+        ch.set_ability(&Ability::Dex, 19);
+        let mut level_up = ch.level_up(thief).unwrap();
+        assert_eq!(level_up.decisions()[1], Decision::AbilityScoreImprovement);
+        level_up.resolve(0, Choice::HitPoints(HitPointChoice::Average), &data).unwrap();
+        level_up.resolve(1, Choice::AbilityScoreImprovement(Improvement::Increase(Ability::Dex, Ability::Dex)), &data).unwrap();
+        assert!(ch.apply_level_up(level_up.clone()).is_err());
+        assert_eq!(ch.level(), 1);
+        level_up.resolve(1, Choice::AbilityScoreImprovement(Improvement::Increase(Ability::Dex, Ability::Con)), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        assert_eq!(*ch.ability(&Ability::Dex), 20);
+        assert_eq!(*ch.ability(&Ability::Con), 11);
+        assert_eq!(ch.improvements(), vec![(2, &Improvement::Increase(Ability::Dex, Ability::Con))]);
+        ch.level_down().unwrap();
+        assert_eq!(*ch.ability(&Ability::Dex), 19);
+        assert_eq!(*ch.ability(&Ability::Con), 10);
+    }
+    #[test]
+    fn test_revising_ability_score_improvement() {
+        let mut data = data_store_with_classes_and_spells();
+        data = add_feats(data);
+        let mut ch = Character::new(&data);
+        let thief = data.get_class("Thief").unwrap();
+        ch.set_class(thief).unwrap();
+        let mut level_up = ch.level_up(thief).unwrap();
+        level_up.resolve(0, Choice::HitPoints(HitPointChoice::Average), &data).unwrap();
+        level_up.resolve(1, Choice::AbilityScoreImprovement(Improvement::Increase(Ability::Int, Ability::Int)), &data).unwrap();
+        ch.apply_level_up(level_up).unwrap();
+        assert_eq!(
+            ch.revise_improvement(2, Improvement::Feat("Even Smarter".to_owned())),
+            Err(Error::PrerequisitesNotMet { feat : "Even Smarter".to_owned() })
+        );
+        assert_eq!(*ch.ability(&Ability::Int), 12);
+        ch.revise_improvement(2, Improvement::Feat("Strong".to_owned())).unwrap();
+        assert_eq!(*ch.ability(&Ability::Int), 10);
+        assert_eq!(*ch.ability(&Ability::Str), 12);
+        ch.level_down().unwrap();
+        assert_eq!(*ch.ability(&Ability::Str), 10);
+    }
+
+    fn data_store_with_classes_and_spells() -> Datastore {
+        let mut data = Datastore::new();
+        data = add_classes(add_spells(data));
+        data
+    }
+}
+
 use crate::datastore::{ SpellSchool, SpellComponent };
 fn add_spells(data : Datastore) -> Datastore {
     let mut data = data;
@@ -383,6 +1971,23 @@ fn add_spells(data : Datastore) -> Datastore {
                 vec![SpellComponent::Verbal, SpellComponent::Material("A shoe".to_owned())].iter().cloned(),
             ),
             duration : "Instantaneous".to_owned(),
+            damage_type : Some(DamageType::Bludgeoning),
+            attack : None,
+        }
+    );
+    data.add_spell(
+        Spell {
+            name : "Spark".to_owned(),
+            long_text : "A tiny zap".to_owned(),
+            level : SpellLevel::Cantrip,
+            school : SpellSchool::Evocation,
+            casting_time : "1 action".to_owned(),
+            components : HashSet::from_iter(
+                vec![SpellComponent::Somatic].iter().cloned(),
+            ),
+            duration : "Instantaneous".to_owned(),
+            damage_type : Some(DamageType::Lightning),
+            attack : Some(SpellAttack { range : 120, damage_dice : 1, damage_die : Die::D10 }),
         }
     );
     data
@@ -410,23 +2015,147 @@ fn add_feats(data : Datastore) -> Datastore {
             prerequisites : vec![FeatPrerequisite::MinimumAbility(Ability::Int, 14)],
         }
     );
+    data.add_feat(
+        Feat {
+            name : "Resilient".to_owned(),
+            long_text : "Hard to put down".to_owned(),
+            effects : vec![FeatEffect::Save(SaveEffect::Proficiency(Ability::Con))],
+            prerequisites : vec![],
+        }
+    );
+    data.add_feat(
+        Feat {
+            name : "Sneaky".to_owned(),
+            long_text : "Never heard coming".to_owned(),
+            effects : vec![FeatEffect::Advantage(RollKind::Skill(Skill::Stealth))],
+            prerequisites : vec![],
+        }
+    );
+    data.add_feat(
+        Feat {
+            name : "Mobile".to_owned(),
+            long_text : "Always on the move".to_owned(),
+            effects : vec![FeatEffect::Speed(SpeedEffect::Bonus(MovementMode::Walk, 10))],
+            prerequisites : vec![],
+        }
+    );
+    data.add_feat(
+        Feat {
+            name : "Alert".to_owned(),
+            long_text : "Always on the lookout".to_owned(),
+            effects : vec![FeatEffect::InitiativeBonus(5), FeatEffect::CannotBeSurprised],
+            prerequisites : vec![],
+        }
+    );
     data
 }
 
 fn add_classes(data : Datastore) -> Datastore {
     let mut data = data;
+    let mut warrior = Class {
+        name : "Warrior".to_owned(),
+        long_text : "A brave fighter".to_owned(),
+        hit_die : Die::D10,
+        saving_throws : vec![Ability::Str, Ability::Con],
+        combat_proficiencies : vec![
+            CombatProficiency::WeaponCategory(WeaponCategory::Simple), 
+            CombatProficiency::WeaponCategory(WeaponCategory::Martial)
+        ],
+        skill_proficiencies : vec![Skill::Athletics],
+        tool_proficiencies : Vec::new(),
+        spell_caster : SpellCaster::None,
+        spellcasting_ability : None,
+        spells_learned : Vec::new(),
+        features : vec![
+            ClassFeature {
+                name : "Fighting Style".to_owned(),
+                long_text : "Everyone fights differently".to_owned(),
+                level : 1,
+                options : vec!["Archery".to_owned(), "Defense".to_owned()],
+                effects : Vec::new(),
+            },
+            ClassFeature {
+                name : "Second Wind".to_owned(),
+                long_text : "Catch your breath".to_owned(),
+                level : 2,
+                options : Vec::new(),
+                effects : Vec::new(),
+            },
+        ],
+        asi_levels : Vec::new(),
+        subclass_level : 3,
+        subclasses : HashMap::new(),
+        starting_equipment : StartingEquipment {
+            choices : vec![
+                vec![vec![StartingItem::AnyWeapon(WeaponCategory::Martial)], vec![StartingItem::Item(ItemKind::Armor, "Tower Shield".to_owned(), 1)]],
+                vec![vec![StartingItem::Item(ItemKind::Armor, "Mithril Mail".to_owned(), 1)]],
+            ],
+            gold_alternative : Currency { gp : 50, ..Currency::default() },
+        },
+    };
+    warrior.add_subclass(
+        Subclass {
+            name : "Champion".to_owned(),
+            long_text : "Simply the best".to_owned(),
+            features : vec![
+                ClassFeature {
+                    name : "Improved Critical".to_owned(),
+                    long_text : "Hits harder more often".to_owned(),
+                    level : 3,
+                    options : Vec::new(),
+                    effects : Vec::new(),
+                },
+            ],
+        }
+    );
+    warrior.add_subclass(
+        Subclass {
+            name : "Berserker".to_owned(),
+            long_text : "Rage!".to_owned(),
+            features : vec![
+                ClassFeature {
+                    name : "Path of Rage".to_owned(),
+                    long_text : "Every berserker rages differently".to_owned(),
+                    level : 3,
+                    options : vec!["Frenzy".to_owned(), "Mindless Rage".to_owned()],
+                    effects : Vec::new(),
+                },
+            ],
+        }
+    );
+    data.add_class(warrior);
     data.add_class(
         Class {
-            name : "Warrior".to_owned(),
-            long_text : "A brave fighter".to_owned(),
-            hit_die : Die::D10,
-            saving_throws : vec![Ability::Str, Ability::Con],
-            combat_proficiencies : vec![
-                CombatProficiency::WeaponCategory(WeaponCategory::Simple), 
-                CombatProficiency::WeaponCategory(WeaponCategory::Martial)
-            ],
-            skill_proficiencies : vec![Skill::Athletics],
+            name : "Bard".to_owned(),
+            long_text : "Knows a bit of everything".to_owned(),
+            hit_die : Die::D8,
+            saving_throws : vec![Ability::Dex, Ability::Cha],
+            combat_proficiencies : Vec::new(),
+            skill_proficiencies : vec![Skill::Performance],
+            tool_proficiencies : Vec::new(),
             spell_caster : SpellCaster::None,
+            spellcasting_ability : None,
+            spells_learned : Vec::new(),
+            features : vec![
+                ClassFeature {
+                    name : "Jack of All Trades".to_owned(),
+                    long_text : "Dabbles in everything".to_owned(),
+                    level : 2,
+                    options : Vec::new(),
+                    effects : vec![FeatureEffect::HalfProficiency(Rounding::Down)],
+                },
+                ClassFeature {
+                    name : "Expertise".to_owned(),
+                    long_text : "Really good at talking".to_owned(),
+                    level : 3,
+                    options : Vec::new(),
+                    effects : vec![FeatureEffect::Expertise(Skill::Persuasion)],
+                },
+            ],
+            asi_levels : Vec::new(),
+            subclass_level : 0,
+            subclasses : HashMap::new(),
+            starting_equipment : StartingEquipment::default(),
         }
     );
     data.add_class(
@@ -439,7 +2168,23 @@ fn add_classes(data : Datastore) -> Datastore {
                 CombatProficiency::WeaponCategory(WeaponCategory::Simple)
             ],
             skill_proficiencies : vec![Skill::Acrobatics],
+            tool_proficiencies : Vec::new(),
             spell_caster : SpellCaster::None,
+            spellcasting_ability : None,
+            spells_learned : Vec::new(),
+            features : vec![
+                ClassFeature {
+                    name : "Second-Story Work".to_owned(),
+                    long_text : "Climbing is second nature".to_owned(),
+                    level : 1,
+                    options : Vec::new(),
+                    effects : vec![FeatureEffect::Speed(SpeedEffect::EqualToWalking(MovementMode::Climb))],
+                },
+            ],
+            asi_levels : vec![2],
+            subclass_level : 0,
+            subclasses : HashMap::new(),
+            starting_equipment : StartingEquipment::default(),
         }
     );
     data.add_class(
@@ -450,7 +2195,47 @@ fn add_classes(data : Datastore) -> Datastore {
             saving_throws : vec![Ability::Int, Ability::Wis],
             combat_proficiencies : Vec::new(),
             skill_proficiencies : Vec::new(),
+            tool_proficiencies : Vec::new(),
             spell_caster : SpellCaster::Full,
+            spellcasting_ability : Some(Ability::Int),
+            spells_learned : vec![2, 1, 1],
+            features : Vec::new(),
+            asi_levels : Vec::new(),
+            subclass_level : 0,
+            subclasses : HashMap::new(),
+            starting_equipment : StartingEquipment::default(),
+        }
+    );
+    data.add_class(
+        Class {
+            name : "Hexer".to_owned(),
+            long_text : "Power comes at a price".to_owned(),
+            hit_die : Die::D8,
+            saving_throws : vec![Ability::Wis, Ability::Cha],
+            combat_proficiencies : vec![
+                CombatProficiency::WeaponCategory(WeaponCategory::Simple)
+            ],
+            skill_proficiencies : Vec::new(),
+            tool_proficiencies : Vec::new(),
+            spell_caster : SpellCaster::Pact,
+            spellcasting_ability : None,
+            spells_learned : Vec::new(),
+            features : vec![
+                ClassFeature {
+                    name : "Dark One's Hide".to_owned(),
+                    long_text : "Shadows cling to the skin".to_owned(),
+                    level : 1,
+                    options : Vec::new(),
+                    effects : vec![
+                        FeatureEffect::DamageDefense(DamageDefense::Resistance, DamageType::Necrotic),
+                        FeatureEffect::DamageDefense(DamageDefense::Vulnerability, DamageType::Radiant),
+                    ],
+                },
+            ],
+            asi_levels : Vec::new(),
+            subclass_level : 0,
+            subclasses : HashMap::new(),
+            starting_equipment : StartingEquipment::default(),
         }
     );
     data
@@ -465,10 +2250,12 @@ fn add_race_with_subraces(data : Datastore) -> Datastore {
             vec![(Ability::Cha, 2)].iter().cloned()
         ),
         size : Size::Medium,
-        speed : 35,
+        speed : Speeds::walking(35),
         languages : vec!["Common".to_owned()],
         skill_proficiencies : Vec::new(),
         combat_proficiencies : Vec::new(),
+        save_effects : Vec::new(),
+        traits : vec![RacialTrait::Darkvision(60)],
         subraces : HashMap::new(),
     };
     halfbreed.add_subrace(
@@ -481,6 +2268,8 @@ fn add_race_with_subraces(data : Datastore) -> Datastore {
             languages : vec!["Angelic".to_owned()],
             skill_proficiencies : vec![Skill::Persuasion],
             combat_proficiencies : vec![CombatProficiency::WeaponCategory(WeaponCategory::Simple)],
+            save_effects : Vec::new(),
+            traits : Vec::new(),
         }
     );
     halfbreed.add_subrace(
@@ -493,6 +2282,14 @@ fn add_race_with_subraces(data : Datastore) -> Datastore {
             languages : vec!["Demonic".to_owned()],
             skill_proficiencies : vec![Skill::Intimidation],
             combat_proficiencies : vec![CombatProficiency::WeaponCategory(WeaponCategory::Martial)],
+            save_effects : Vec::new(),
+            traits : vec![
+                RacialTrait::Darkvision(120),
+                RacialTrait::DamageDefense(DamageDefense::Resistance, DamageType::Fire),
+                RacialTrait::ConditionImmunity("Poisoned".to_owned()),
+                RacialTrait::InnateSpell(InnateSpell { spell : "Magic Boot".to_owned(), ability : Ability::Cha, level : 3, uses_per_day : 1 }),
+                RacialTrait::NaturalWeapon(NaturalWeapon { name : "Claws".to_owned(), damage_die : Die::D6, damage_type : DamageType::Slashing }),
+            ],
         }
     );
     data.add_race(halfbreed);
@@ -509,10 +2306,12 @@ fn add_races(data : Datastore) -> Datastore {
                 vec![(Ability::Wis, 2)].iter().cloned()
             ),
             size : Size::Medium,
-            speed : 40,
+            speed : Speeds { walk : 40, fly : 60, hover : true, ..Speeds::default() },
             languages : vec!["Angelic".to_owned()],
             skill_proficiencies : vec![Skill::Persuasion],
             combat_proficiencies : vec![CombatProficiency::Weapon("Beau's Bow".to_owned())],
+            save_effects : Vec::new(),
+            traits : Vec::new(),
             subraces : HashMap::new(),
         }
     );      
@@ -524,10 +2323,12 @@ fn add_races(data : Datastore) -> Datastore {
                 vec![(Ability::Con, 2)].iter().cloned()
             ),
             size : Size::Large,
-            speed : 30,
+            speed : Speeds::walking(30),
             languages : vec!["Demonic".to_owned()],
             skill_proficiencies : vec![Skill::Intimidation],
             combat_proficiencies : vec![CombatProficiency::Weapon("Bloodsword".to_owned())],
+            save_effects : vec![SaveEffect::Advantage(Some(Ability::Con), Some("poison".to_owned()))],
+            traits : Vec::new(),
             subraces : HashMap::new(),
         }
     );    
@@ -542,6 +2343,26 @@ fn add_equipment(data : Datastore) -> Datastore {
             category : WeaponCategory::Martial,
             range_category : WeaponRange::Melee,
             reach : 5,
+            damage_dice : 1,
+            damage_die : Die::D8,
+            damage_type : DamageType::Slashing,
+            properties : vec![WeaponProperty::Versatile(Die::D10)],
+            weight : 3 * POUND,
+            price : Currency { gp : 15, ..Currency::default() },
+        }
+    );
+    data.add_weapon(
+        Weapon {
+            name : "Dagger".to_owned(),
+            category : WeaponCategory::Simple,
+            range_category : WeaponRange::Melee,
+            reach : 5,
+            damage_dice : 1,
+            damage_die : Die::D4,
+            damage_type : DamageType::Piercing,
+            properties : vec![WeaponProperty::Finesse, WeaponProperty::Light, WeaponProperty::Thrown],
+            weight : POUND,
+            price : Currency { gp : 2, ..Currency::default() },
         }
     );
     data.add_weapon(
@@ -550,13 +2371,236 @@ fn add_equipment(data : Datastore) -> Datastore {
             category : WeaponCategory::Simple,
             range_category : WeaponRange::Ranged,
             reach : 60,
+            damage_dice : 1,
+            damage_die : Die::D6,
+            damage_type : DamageType::Piercing,
+            properties : Vec::new(),
+            weight : 2 * POUND,
+            price : Currency { gp : 25, ..Currency::default() },
         }
     );
     data.add_armor(
         Armor {
             name : "Power Armor".to_owned(),
             category : ArmorCategory::Heavy,
+            armor_class : 18,
+            stealth_disadvantage : true,
+            strength_requirement : Some(15),
+            weight : 65 * POUND,
+            price : Currency { gp : 1500, ..Currency::default() },
+        }
+    );
+    data.add_armor(
+        Armor {
+            name : "Mithril Mail".to_owned(),
+            category : ArmorCategory::Medium,
+            armor_class : 14,
+            stealth_disadvantage : false,
+            strength_requirement : None,
+            weight : 20 * POUND,
+            price : Currency { gp : 800, ..Currency::default() },
+        }
+    );
+    data.add_armor(
+        Armor {
+            name : "Tower Shield".to_owned(),
+            category : ArmorCategory::Shield,
+            armor_class : 2,
+            stealth_disadvantage : false,
+            strength_requirement : None,
+            weight : 6 * POUND,
+            price : Currency { gp : 10, ..Currency::default() },
+        }
+    );
+    data.add_item(
+        Item {
+            name : "Backpack".to_owned(),
+            long_text : "Holds all your stuff".to_owned(),
+            weight : 5 * POUND,
+            price : Currency { gp : 2, ..Currency::default() },
+            capacity : Some(30 * POUND),
+        }
+    );
+    data.add_item(
+        Item {
+            name : "Rope".to_owned(),
+            long_text : "50 feet of hempen rope".to_owned(),
+            weight : 10 * POUND,
+            price : Currency { gp : 1, ..Currency::default() },
+            capacity : None,
+        }
+    );
+    data.add_item(
+        Item {
+            name : "Arrow".to_owned(),
+            long_text : "Pointy".to_owned(),
+            weight : POUND / 20,
+            price : Currency { cp : 5, ..Currency::default() },
+            capacity : None,
+        }
+    );
+    data
+}
+
+fn add_magic_items(data : Datastore) -> Datastore {
+    let mut data = data;
+    data.add_magic_item(
+        MagicItem {
+            name : "Bloodsword +2".to_owned(),
+            long_text : "Thirstier than usual".to_owned(),
+            rarity : Rarity::Rare,
+            base : MagicItemBase::Weapon("Bloodsword".to_owned()),
+            attunement : Attunement::NotRequired,
+            effects : vec![MagicEffect::WeaponBonus(2)],
+        }
+    );
+    data.add_magic_item(
+        MagicItem {
+            name : "Mithril Mail +1".to_owned(),
+            long_text : "Shinier than usual".to_owned(),
+            rarity : Rarity::Rare,
+            base : MagicItemBase::Armor("Mithril Mail".to_owned()),
+            attunement : Attunement::NotRequired,
+            effects : vec![MagicEffect::ArmorClassBonus(1)],
+        }
+    );
+    data.add_magic_item(
+        MagicItem {
+            name : "Ring of Fire Resistance".to_owned(),
+            long_text : "Warm to the touch".to_owned(),
+            rarity : Rarity::Rare,
+            base : MagicItemBase::Wondrous(0),
+            attunement : Attunement::Required(Vec::new()),
+            effects : vec![MagicEffect::DamageDefense(DamageDefense::Resistance, DamageType::Fire)],
+        }
+    );
+    data.add_magic_item(
+        MagicItem {
+            name : "Gauntlets of Ogre Power".to_owned(),
+            long_text : "Smells like onions".to_owned(),
+            rarity : Rarity::Uncommon,
+            base : MagicItemBase::Wondrous(POUND),
+            attunement : Attunement::Required(vec![]),
+            effects : vec![MagicEffect::AbilityOverride(Ability::Str, 19)],
+        }
+    );
+    data.add_magic_item(
+        MagicItem {
+            name : "Cloak of Sneaking".to_owned(),
+            long_text : "Also kicks things quietly".to_owned(),
+            rarity : Rarity::Common,
+            base : MagicItemBase::Wondrous(POUND),
+            attunement : Attunement::NotRequired,
+            effects : vec![MagicEffect::SkillBonus(Skill::Stealth, 2), MagicEffect::Spell("Magic Boot".to_owned())],
+        }
+    );
+    data.add_magic_item(
+        MagicItem {
+            name : "Cloak of Protection".to_owned(),
+            long_text : "Keeps the rain and worse off".to_owned(),
+            rarity : Rarity::Uncommon,
+            base : MagicItemBase::Wondrous(POUND),
+            attunement : Attunement::Required(vec![]),
+            effects : vec![MagicEffect::ArmorClassBonus(1), MagicEffect::Save(SaveEffect::Bonus(1))],
+        }
+    );
+    data.add_magic_item(
+        MagicItem {
+            name : "Staff of the Mage".to_owned(),
+            long_text : "Not for warriors".to_owned(),
+            rarity : Rarity::Legendary,
+            base : MagicItemBase::Wondrous(4 * POUND),
+            attunement : Attunement::Required(vec![AttunementRequirement::Class("Mage".to_owned())]),
+            effects : vec![],
+        }
+    );
+    data
+}
+
+fn add_backgrounds(data : Datastore) -> Datastore {
+    let mut data = data;
+    data.add_background(
+        Background {
+            name : "Sailor".to_owned(),
+            long_text : "Knows the ropes".to_owned(),
+            tool_proficiencies : vec![ToolProficiency::Tool("Sailboat".to_owned()), ToolProficiency::AnyOf(ToolCategory::ArtisanTools)],
+            starting_equipment : StartingEquipment {
+                choices : vec![vec![vec![StartingItem::Item(ItemKind::Item, "Rope".to_owned(), 1)]]],
+                gold_alternative : Currency { gp : 10, ..Currency::default() },
+            },
+        }
+    );
+    data
+}
+
+fn add_conditions(data : Datastore) -> Datastore {
+    let mut data = data;
+    data.add_condition(
+        Condition {
+            name : "Poisoned".to_owned(),
+            long_text : "Feeling sick".to_owned(),
+            effects : vec![ConditionEffect::AttackDisadvantage, ConditionEffect::CheckDisadvantage],
+        }
+    );
+    data.add_condition(
+        Condition {
+            name : "Invisible".to_owned(),
+            long_text : "Now you see me".to_owned(),
+            effects : vec![ConditionEffect::AttackAdvantage],
+        }
+    );
+    data.add_condition(
+        Condition {
+            name : "Restrained".to_owned(),
+            long_text : "Tied up".to_owned(),
+            effects : vec![ConditionEffect::SpeedZero, ConditionEffect::AttackDisadvantage],
+        }
+    );
+    data.add_condition(
+        Condition {
+            name : "Cursed".to_owned(),
+            long_text : "Bad luck".to_owned(),
+            effects : vec![ConditionEffect::AttackModifier(-1), ConditionEffect::CheckModifier(-2)],
+        }
+    );
+    data.add_condition(
+        Condition {
+            name : "Blessed".to_owned(),
+            long_text : "Good luck".to_owned(),
+            effects : vec![ConditionEffect::SaveModifier(1)],
+        }
+    );
+    data.add_condition(
+        Condition {
+            name : "Petrified".to_owned(),
+            long_text : "Turned to stone".to_owned(),
+            effects : vec![
+                ConditionEffect::SpeedZero,
+                ConditionEffect::DamageDefense(DamageDefense::Resistance, DamageType::Necrotic),
+                ConditionEffect::DamageDefense(DamageDefense::Resistance, DamageType::Slashing),
+                ConditionEffect::DamageDefense(DamageDefense::Immunity, DamageType::Poison),
+            ],
         }
     );
     data
-}
\ No newline at end of file
+}
+
+fn add_tools(data : Datastore) -> Datastore {
+    let mut data = data;
+    for (name, category, ability) in [
+        ("Smith's Tools", ToolCategory::ArtisanTools, Ability::Str),
+        ("Weaver's Tools", ToolCategory::ArtisanTools, Ability::Dex),
+        ("Thieves' Tools", ToolCategory::Other, Ability::Dex),
+        ("Sailboat", ToolCategory::Vehicle, Ability::Wis),
+    ] {
+        data.add_tool(
+            Tool {
+                name : name.to_owned(),
+                long_text : String::new(),
+                category,
+                ability,
+            }
+        );
+    }
+    data
+}
@@ -0,0 +1,70 @@
+use super::{ Character, Ability, Modifier };
+use crate::datastore::{ Tool, ToolCategory, ToolProficiency, SkillLevel };
+use crate::error::{ Error, DataKind };
+
+impl<'d> Character<'d> {
+    /// Sets the proficiency of the character with a tool from the datastore, regardless of their class or background
+    pub fn set_tool_level(&mut self, tool : &Tool, level : SkillLevel) {
+        self.tools.insert(tool.name.clone(), level);
+    }
+    /// Fills one of the choices of any tool of a category the class or background of the character offers
+    pub fn choose_tool(&mut self, tool : &Tool) -> Result<(), Error> {
+        if !self.open_tool_choices().contains(&tool.category) {
+            return Err(Error::Validation(format!("Character has no choice of {:?} left.", tool.category)));
+        }
+        self.chosen_tools.push(tool.name.clone());
+        Ok(())
+    }
+    /// Returns the categories of the tool proficiencies the character still has to choose
+    pub fn open_tool_choices(&self) -> Vec<ToolCategory> {
+        let mut open : Vec<ToolCategory> = self.granted_tool_proficiencies().into_iter()
+            .filter_map( |proficiency| match proficiency {
+                ToolProficiency::AnyOf(category) => Some(*category),
+                ToolProficiency::Tool(_) => None,
+            })
+            .collect();
+        for chosen in self.chosen_tools.iter().filter_map( |tool| self.data.get_tool(tool) ) {
+            if let Some(index) = open.iter().position( |category| *category == chosen.category ) {
+                open.remove(index);
+            }
+        }
+        open
+    }
+    /// Returns the highest proficiency of the character with the tool, from themself, their class, their background,
+    /// the tools they chose and features like Jack of All Trades
+    pub fn tool_level(&self, tool : &str) -> Result<SkillLevel, Error> {
+        if self.data.get_tool(tool).is_none() {
+            return Err(Error::missing(DataKind::Tool, tool));
+        }
+        let mut levels = vec![self.half_proficiency()];
+        if let Some(level) = self.tools.get(tool) {
+            levels.push(*level);
+        }
+        let granted = self.granted_tool_proficiencies().into_iter()
+            .any( |proficiency| *proficiency == ToolProficiency::Tool(tool.to_owned()) );
+        if granted || self.chosen_tools.iter().any( |chosen| chosen == tool ) {
+            levels.push(SkillLevel::Proficient);
+        }
+        Ok(levels.into_iter().max().unwrap_or(SkillLevel::None))
+    }
+    /// Returns the modifier for a check with the tool using the ability usually used with it
+    pub fn tool_mod_default(&self, tool : &str) -> Result<Modifier, Error> {
+        match self.data.get_tool(tool) {
+            Some(tool_data) => self.tool_mod(&tool_data.ability, tool),
+            None => Err(Error::missing(DataKind::Tool, tool)),
+        }
+    }
+    pub fn tool_mod(&self, ability : &Ability, tool : &str) -> Result<Modifier, Error> {
+        Ok(Ability::score_to_mod(self.ability(ability))
+        +
+        self.proficiency_modifier(&self.tool_level(tool)?)
+        +
+        self.condition_check_modifier())
+    }
+    /// Returns the tool proficiencies the first class and the background of the character grant
+    fn granted_tool_proficiencies(&self) -> Vec<&'d ToolProficiency> {
+        let class = self.primary_class().map( |class| &class.tool_proficiencies );
+        let background = self.data.get_background(&self.background).map( |background| &background.tool_proficiencies );
+        class.into_iter().chain(background).flatten().collect()
+    }
+}
@@ -0,0 +1,56 @@
+use super::Character;
+use crate::datastore::{ RacialTrait, InnateSpell, NaturalWeapon };
+use crate::error::{ Error, DataKind };
+
+impl<'d> Character<'d> {
+    /// Returns the traits of the race and subrace of the character
+    pub fn racial_traits(&self) -> Vec<&'d RacialTrait> {
+        let mut traits = Vec::new();
+        if let Some(race) = self.data.get_race(&self.race) {
+            traits.extend(race.traits.iter());
+            if let Some(subrace) = race.get_subrace(&self.subrace) {
+                traits.extend(subrace.traits.iter());
+            }
+        }
+        traits
+    }
+    /// Returns the range of the darkvision of the character in feet, if they have any
+    pub fn darkvision(&self) -> Option<u16> {
+        self.racial_traits().into_iter()
+            .filter_map( |racial_trait| match racial_trait { RacialTrait::Darkvision(range) => Some(*range), _ => None } )
+            .max()
+    }
+    /// Returns the names of the conditions that cannot affect the character
+    pub fn condition_immunities(&self) -> Vec<&'d str> {
+        self.racial_traits().into_iter()
+            .filter_map( |racial_trait| match racial_trait { RacialTrait::ConditionImmunity(condition) => Some(condition.as_str()), _ => None } )
+            .collect()
+    }
+    pub fn natural_weapons(&self) -> Vec<&'d NaturalWeapon> {
+        self.racial_traits().into_iter()
+            .filter_map( |racial_trait| match racial_trait { RacialTrait::NaturalWeapon(weapon) => Some(weapon), _ => None } )
+            .collect()
+    }
+    /// Returns the innate spells the character can cast at their level, together with how many uses they have left
+    pub fn innate_spells(&self) -> Vec<(&'d InnateSpell, u8)> {
+        self.racial_traits().into_iter()
+            .filter_map( |racial_trait| match racial_trait { RacialTrait::InnateSpell(spell) => Some(spell), _ => None } )
+            .filter( |spell| spell.level <= self.level )
+            .map( |spell| (spell, spell.uses_per_day.saturating_sub(*self.used_innate_spells.get(&spell.spell).unwrap_or(&0))) )
+            .collect()
+    }
+    /// Uses up one of the daily uses of an innate spell, which are recovered on a long rest
+    pub fn cast_innate_spell(&mut self, spell : &str) -> Result<(), Error> {
+        if self.data.get_spell(spell).is_none() {
+            return Err(Error::missing(DataKind::Spell, spell));
+        }
+        match self.innate_spells().into_iter().find( |(innate, _)| innate.spell == spell ) {
+            Some((_, 0)) => Err(Error::NoUsesLeft(spell.to_owned())),
+            Some(_) => {
+                *self.used_innate_spells.entry(spell.to_owned()).or_insert(0) += 1;
+                Ok(())
+            },
+            None => Err(Error::Validation(format!("Character cannot cast \"{}\" innately.", spell))),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use crate::character::{ Ability, AbilityScore, Size, Speed, Die };
+use crate::character::{ Ability, AbilityScore, Size, Speed, Die, Currency };
 
 type AbilityArray = HashMap<Ability, AbilityScore>;
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
@@ -9,16 +9,70 @@ pub enum WeaponRange{ Melee, Ranged }
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum ArmorCategory { Light, Medium, Heavy, Shield }
 pub type Reach = u16;
+/// Properties of weapons that change how they can be used in an attack
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum WeaponProperty {
+    /// Attacks can use dexterity instead of strength
+    Finesse,
+    /// Allows fighting with a weapon in each hand
+    Light,
+    Thrown,
+    /// The weapon has to be wielded with both hands
+    TwoHanded,
+    /// The weapon can be wielded with both hands to roll this die for damage instead
+    Versatile(Die),
+}
+/// The kinds of d20 rolls a character makes, which e.g. feats can grant advantage on
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum RollKind {
+    Attack,
+    /// Any check with the ability, including skill checks
+    Check(Ability),
+    Skill(Skill),
+    Save(Ability),
+}
 
 
 mod races;
-pub use races::{ Race, Subrace };
+pub use races::{ Race, Subrace, Trait as RacialTrait, InnateSpell, NaturalWeapon };
+
+mod movement;
+#[allow(unused_imports)]
+pub use movement::{ MovementMode, Speeds, SpeedEffect };
+
+mod damage;
+pub use damage::{ DamageType, DamageDefense };
 
 mod spells;
-pub use spells::{ Spell, SpellComponent, SpellSchool, SpellLevel, SpellCaster, SpellSlots };
+#[allow(unused_imports)]
+pub use spells::{ Spell, SpellAttack, SpellComponent, SpellSchool, SpellLevel, SpellCaster, SpellSlots, SpellProgression, SlotRecovery };
 
 mod skills;
-pub use skills::{ Skill, SkillLevel, CombatProficiency };
+pub use skills::{ Skill, SkillLevel, Rounding, SaveEffect, CombatProficiency };
+
+mod tools;
+pub use tools::{ Tool, ToolCategory, ToolProficiency };
+
+mod classes;
+#[allow(unused_imports)]
+pub use classes::{ Class, Subclass, ClassFeature, FeatureEffect };
+
+mod items;
+pub use items::{ Item, Weight, POUND };
+
+mod magic_items;
+#[allow(unused_imports)]
+pub use magic_items::{ MagicItem, Rarity, Base as MagicItemBase, Attunement, AttunementRequirement, Effect as MagicEffect };
+
+mod equipment;
+#[allow(unused_imports)]
+pub use equipment::{ StartingEquipment, StartingItem, EquipmentBundle, Background };
+
+mod conditions;
+pub use conditions::{ Condition, Effect as ConditionEffect };
+
+mod advancement;
+pub use advancement::{ Advancement, ExperienceTable };
 
 mod feats;
 pub use feats::{ Feat, Effect as FeatEffect, Prerequisite as FeatPrerequisite };
@@ -28,43 +82,70 @@ pub struct Weapon {
     pub category : WeaponCategory,
     pub range_category : WeaponRange,
     pub reach : Reach,
+    /// The number of damage dice rolled on a hit
+    pub damage_dice : u8,
+    pub damage_die : Die,
+    pub damage_type : DamageType,
+    pub properties : Vec<WeaponProperty>,
+    pub weight : Weight,
+    pub price : Currency,
 }
 
 pub struct Armor {
     pub name : String,
     pub category : ArmorCategory,
+    /// The base armor class of the armor, or the bonus to armor class for shields
+    pub armor_class : i8,
+    /// Whether wearing the armor imposes disadvantage on stealth checks
+    pub stealth_disadvantage : bool,
+    /// The strength score below which wearing the armor reduces speed by 10 feet
+    pub strength_requirement : Option<AbilityScore>,
+    pub weight : Weight,
+    pub price : Currency,
 }
 
 pub struct Datastore {
     races : HashMap<String, Race>,
     weapons : HashMap<String, Weapon>,
     armors : HashMap<String, Armor>,
+    items : HashMap<String, Item>,
+    magic_items : HashMap<String, MagicItem>,
     classes : HashMap<String, Class>,
+    backgrounds : HashMap<String, Background>,
+    tools : HashMap<String, Tool>,
     feats : HashMap<String, Feat>,
     spells : HashMap<String, Spell>,
-}
-
-pub struct Class {
-    pub name : String,
-    pub long_text : String,
-    pub hit_die : Die,
-    pub saving_throws : Vec<Ability>,
-    pub combat_proficiencies : Vec<CombatProficiency>,
-    pub skill_proficiencies : Vec<Skill>,
-    pub spell_caster : SpellCaster,
+    spell_progressions : HashMap<SpellCaster, SpellProgression>,
+    experience_table : ExperienceTable,
+    conditions : HashMap<String, Condition>,
+    exhaustion : Vec<Vec<ConditionEffect>>,
 }
 
 impl Datastore {
-    /// Creates a new data store without any data in it
+    /// Creates a new data store without any data in it except for the standard spell slot progressions,
+    /// experience table and levels of exhaustion
     pub fn new() -> Self { 
-        Datastore {
+        let mut data = Datastore {
             races : HashMap::new(),
             weapons : HashMap::new(),
             armors : HashMap::new(),
+            items : HashMap::new(),
+            magic_items : HashMap::new(),
             classes : HashMap::new(),
+            backgrounds : HashMap::new(),
+            tools : HashMap::new(),
             feats : HashMap::new(),
             spells : HashMap::new(),
-        }
+            spell_progressions : HashMap::new(),
+            experience_table : ExperienceTable::standard(),
+            conditions : HashMap::new(),
+            exhaustion : conditions::standard_exhaustion(),
+        };
+        data.add_spell_progression(SpellCaster::Full, SpellProgression::full());
+        data.add_spell_progression(SpellCaster::Half, SpellProgression::half());
+        data.add_spell_progression(SpellCaster::Third, SpellProgression::third());
+        data.add_spell_progression(SpellCaster::Pact, SpellProgression::pact());
+        data
     }
     pub fn add_race(&mut self, race : Race) {
         self.races.insert(race.name.clone(), race);
@@ -75,15 +156,48 @@ impl Datastore {
     pub fn add_armor(&mut self, armor : Armor) {
         self.armors.insert(armor.name.clone(), armor);
     }
+    pub fn add_item(&mut self, item : Item) {
+        self.items.insert(item.name.clone(), item);
+    }
+    pub fn add_magic_item(&mut self, item : MagicItem) {
+        self.magic_items.insert(item.name.clone(), item);
+    }
     pub fn add_class(&mut self, class : Class) {
         self.classes.insert(class.name.clone(), class);
     }
+    pub fn add_background(&mut self, background : Background) {
+        self.backgrounds.insert(background.name.clone(), background);
+    }
+    pub fn add_tool(&mut self, tool : Tool) {
+        self.tools.insert(tool.name.clone(), tool);
+    }
     pub fn add_feat(&mut self, feat : Feat) {
         self.feats.insert(feat.name.clone(), feat);
     }
     pub fn add_spell(&mut self, spell : Spell) {
         self.spells.insert(spell.name.clone(), spell);
     }
+    /// Defines the spell slots of a caster type, replacing any previous definition
+    pub fn add_spell_progression(&mut self, caster : SpellCaster, progression : SpellProgression) {
+        self.spell_progressions.insert(caster, progression);
+    }
+    /// Replaces the experience points needed to reach each level
+    pub fn set_experience_table(&mut self, table : ExperienceTable) {
+        self.experience_table = table;
+    }
+    pub fn experience_table(&self) -> &ExperienceTable {
+        &self.experience_table
+    }
+    pub fn add_condition(&mut self, condition : Condition) {
+        self.conditions.insert(condition.name.clone(), condition);
+    }
+    /// Replaces the effects gained at each level of exhaustion, starting with level 1
+    pub fn set_exhaustion(&mut self, exhaustion : Vec<Vec<ConditionEffect>>) {
+        self.exhaustion = exhaustion;
+    }
+    pub fn exhaustion(&self) -> &[Vec<ConditionEffect>] {
+        &self.exhaustion
+    }
     pub fn get_race(&self, race : &str) -> Option<&Race> {
         self.races.get(race)
     }
@@ -93,14 +207,35 @@ impl Datastore {
     pub fn get_armor(&self, armor : &str) -> Option<&Armor> {
         self.armors.get(armor)
     }
+    pub fn get_item(&self, item : &str) -> Option<&Item> {
+        self.items.get(item)
+    }
+    pub fn get_magic_item(&self, item : &str) -> Option<&MagicItem> {
+        self.magic_items.get(item)
+    }
     pub fn get_class(&self, class : &str) -> Option<&Class> {
         self.classes.get(class)
     }
+    pub fn get_background(&self, background : &str) -> Option<&Background> {
+        self.backgrounds.get(background)
+    }
+    pub fn get_tool(&self, tool : &str) -> Option<&Tool> {
+        self.tools.get(tool)
+    }
+    /// Returns all tools of the category, e.g. to offer a choice of any one artisan's tools
+    pub fn tools_in_category(&self, category : ToolCategory) -> Vec<&Tool> {
+        self.tools.values().filter( |tool| tool.category == category ).collect()
+    }
     pub fn get_feat(&self, feat : &str) -> Option<&Feat> {
         self.feats.get(feat)
     }
     pub fn get_spell(&self, spell : &str) -> Option<&Spell> {
         self.spells.get(spell)
     }
+    pub fn get_condition(&self, condition : &str) -> Option<&Condition> {
+        self.conditions.get(condition)
+    }
+    pub fn get_spell_progression(&self, caster : &SpellCaster) -> Option<&SpellProgression> {
+        self.spell_progressions.get(caster)
+    }
 }
-
@@ -0,0 +1,33 @@
+use crate::error::Error;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Advancement { Experience, Milestone }
+
+/// The experience points needed to reach each level
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ExperienceTable {
+    /// The experience points needed for each level, starting with level 1
+    pub thresholds : Vec<u32>,
+}
+
+impl ExperienceTable {
+    pub fn standard() -> Self {
+        ExperienceTable {
+            thresholds : vec![
+                0, 300, 900, 2700, 6500, 14000, 23000, 34000, 48000, 64000,
+                85000, 100000, 120000, 140000, 165000, 195000, 225000, 265000, 305000, 355000,
+            ],
+        }
+    }
+    /// Returns the highest level that can be reached with the experience points
+    pub fn level_for(&self, experience : u32) -> i8 {
+        self.thresholds.iter().take_while( |threshold| **threshold <= experience ).count() as i8
+    }
+    /// Returns the experience points needed to reach the level
+    pub fn experience_for(&self, level : i8) -> Result<u32, Error> {
+        if level < 1 || level as usize > self.thresholds.len() {
+            return Err(Error::InvalidLevel(level));
+        }
+        Ok(self.thresholds[(level - 1) as usize])
+    }
+}
@@ -0,0 +1,77 @@
+use super::{ Ability, Die, DamageType, DamageDefense, Skill, SaveEffect, SpeedEffect, Rounding, CombatProficiency, SpellCaster, StartingEquipment, ToolProficiency };
+use std::collections::HashMap;
+
+pub struct Class {
+    pub name : String,
+    pub long_text : String,
+    pub hit_die : Die,
+    pub saving_throws : Vec<Ability>,
+    pub combat_proficiencies : Vec<CombatProficiency>,
+    pub skill_proficiencies : Vec<Skill>,
+    pub tool_proficiencies : Vec<ToolProficiency>,
+    pub spell_caster : SpellCaster,
+    pub spellcasting_ability : Option<Ability>,
+    /// The number of new spells learned on reaching each class level, starting with level 1
+    pub spells_learned : Vec<u8>,
+    pub features : Vec<ClassFeature>,
+    /// The class levels at which an ability score improvement or a feat is gained
+    pub asi_levels : Vec<i8>,
+    /// The class level at which one of the subclasses has to be chosen
+    pub subclass_level : i8,
+    pub subclasses : HashMap<String, Subclass>,
+    pub starting_equipment : StartingEquipment,
+}
+
+pub struct Subclass {
+    pub name : String,
+    pub long_text : String,
+    pub features : Vec<ClassFeature>,
+}
+
+/// A feature gained on reaching a certain class level. If it has options,
+/// one of them has to be chosen when the feature is gained.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ClassFeature {
+    pub name : String,
+    pub long_text : String,
+    pub level : i8,
+    pub options : Vec<String>,
+    pub effects : Vec<FeatureEffect>,
+}
+
+/// What a class feature does for the character beyond its description
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum FeatureEffect {
+    /// Adds half the proficiency bonus to ability checks, including initiative, that do not already include it
+    HalfProficiency(Rounding),
+    SkillProficiency(Skill),
+    /// Doubles the proficiency bonus for checks with the skill
+    Expertise(Skill),
+    Save(SaveEffect),
+    Speed(SpeedEffect),
+    DamageDefense(DamageDefense, DamageType),
+}
+
+impl Class {
+    pub fn add_subclass(&mut self, subclass : Subclass) {
+        self.subclasses.insert(subclass.name.clone(), subclass);
+    }
+    pub fn get_subclass(&self, subclass : &str) -> Option<&Subclass> {
+        self.subclasses.get(subclass)
+    }
+    /// Returns the features of the class gained on reaching exactly the specified class level
+    pub fn features_at(&self, level : i8) -> Vec<&ClassFeature> {
+        self.features.iter().filter( |feature| feature.level == level ).collect()
+    }
+    /// Returns the number of new spells learned on reaching the specified class level
+    pub fn spells_learned_at(&self, level : i8) -> u8 {
+        if level < 1 { 0 } else { self.spells_learned.get((level - 1) as usize).cloned().unwrap_or(0) }
+    }
+}
+
+impl Subclass {
+    /// Returns the features of the subclass gained on reaching exactly the specified class level
+    pub fn features_at(&self, level : i8) -> Vec<&ClassFeature> {
+        self.features.iter().filter( |feature| feature.level == level ).collect()
+    }
+}
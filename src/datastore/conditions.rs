@@ -0,0 +1,44 @@
+use super::{ DamageType, DamageDefense };
+
+/// What a condition does to the character suffering from it
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Effect {
+    AttackAdvantage,
+    AttackDisadvantage,
+    /// Advantage on ability checks, including skill checks
+    CheckAdvantage,
+    /// Disadvantage on ability checks, including skill checks
+    CheckDisadvantage,
+    SaveAdvantage,
+    SaveDisadvantage,
+    /// A flat bonus or penalty to attack rolls
+    AttackModifier(i8),
+    /// A flat bonus or penalty to ability checks, including skill checks
+    CheckModifier(i8),
+    /// A flat bonus or penalty to saving throws
+    SaveModifier(i8),
+    SpeedZero,
+    HalveSpeed,
+    HalveHitPointMaximum,
+    DamageDefense(DamageDefense, DamageType),
+}
+
+pub struct Condition {
+    pub name : String,
+    pub long_text : String,
+    pub effects : Vec<Effect>,
+}
+
+/// The effects gained at each level of exhaustion, starting with level 1. They add up, so that
+/// a character with three levels of exhaustion suffers from the effects of all of the first three.
+pub fn standard_exhaustion() -> Vec<Vec<Effect>> {
+    vec![
+        vec![Effect::CheckDisadvantage],
+        vec![Effect::HalveSpeed],
+        vec![Effect::AttackDisadvantage, Effect::SaveDisadvantage],
+        vec![Effect::HalveHitPointMaximum],
+        vec![Effect::SpeedZero],
+        // The sixth level of exhaustion is death
+        vec![],
+    ]
+}
@@ -0,0 +1,27 @@
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum DamageType {
+    Acid,
+    Bludgeoning,
+    Cold,
+    Fire,
+    Force,
+    Lightning,
+    Necrotic,
+    Piercing,
+    Poison,
+    Psychic,
+    Radiant,
+    Slashing,
+    Thunder,
+}
+
+/// How a character is affected by damage of a certain type
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum DamageDefense {
+    /// Halves the damage
+    Resistance,
+    /// Prevents the damage altogether
+    Immunity,
+    /// Doubles the damage
+    Vulnerability,
+}
@@ -0,0 +1,29 @@
+use super::{ WeaponCategory, ToolProficiency };
+use crate::character::{ ItemKind, Currency };
+
+/// Something a character can start with
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum StartingItem {
+    Item(ItemKind, String, u32),
+    /// Any one weapon of the category, picked when the starting equipment is taken
+    AnyWeapon(WeaponCategory),
+}
+
+/// A set of items that are granted together
+pub type EquipmentBundle = Vec<StartingItem>;
+
+/// The equipment a class or background starts with
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct StartingEquipment {
+    /// Each entry is a choice between several bundles, entries with a single bundle are always granted
+    pub choices : Vec<Vec<EquipmentBundle>>,
+    /// The money a character can start with instead of the equipment
+    pub gold_alternative : Currency,
+}
+
+pub struct Background {
+    pub name : String,
+    pub long_text : String,
+    pub tool_proficiencies : Vec<ToolProficiency>,
+    pub starting_equipment : StartingEquipment,
+}
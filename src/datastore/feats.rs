@@ -1,4 +1,4 @@
-use super::{ Ability, AbilityScore, Skill };
+use super::{ Ability, AbilityScore, Skill, SaveEffect, SpeedEffect, RollKind };
 
 pub struct Feat {
     pub name : String,
@@ -12,6 +12,11 @@ pub struct Feat {
 pub enum Effect {
     AbilityIncrease(Ability, AbilityScore),
     SkillProficiency(Skill),
+    Advantage(RollKind),
+    Save(SaveEffect),
+    Speed(SpeedEffect),
+    InitiativeBonus(i8),
+    CannotBeSurprised,
 }
 
 pub enum Prerequisite {
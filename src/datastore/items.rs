@@ -0,0 +1,16 @@
+use crate::character::Currency;
+
+/// Weights are measured in hundredths of a pound, so that e.g. coins and ammunition can be weighed exactly
+pub type Weight = u32;
+
+pub const POUND : Weight = 100;
+
+/// Any piece of equipment that is neither a weapon nor armor
+pub struct Item {
+    pub name : String,
+    pub long_text : String,
+    pub weight : Weight,
+    /// The weight an item can hold if other items can be put into it
+    pub capacity : Option<Weight>,
+    pub price : Currency,
+}
@@ -0,0 +1,50 @@
+use super::{ Ability, AbilityScore, Skill, SaveEffect, Weight, DamageType, DamageDefense };
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub enum Rarity { Common, Uncommon, Rare, VeryRare, Legendary, Artifact }
+
+/// What a magic item is made from, which determines e.g. its weight
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Base {
+    Weapon(String),
+    Armor(String),
+    /// Wondrous items like rings or cloaks only have a weight
+    Wondrous(Weight),
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Attunement {
+    NotRequired,
+    /// Only characters meeting at least one of the requirements can attune to the item, anyone if there are none
+    Required(Vec<AttunementRequirement>),
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum AttunementRequirement {
+    Class(String),
+    Race(String),
+}
+
+/// What a magic item does while it is equipped and, if it requires attunement, attuned
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Effect {
+    /// A bonus to attack rolls with the weapon the item is based on
+    WeaponBonus(i8),
+    ArmorClassBonus(i8),
+    /// Sets the ability score to the value unless it is already higher
+    AbilityOverride(Ability, AbilityScore),
+    SkillBonus(Skill, i8),
+    /// The name of a spell the item allows the character to cast
+    Spell(String),
+    Save(SaveEffect),
+    DamageDefense(DamageDefense, DamageType),
+}
+
+pub struct MagicItem {
+    pub name : String,
+    pub long_text : String,
+    pub rarity : Rarity,
+    pub base : Base,
+    pub attunement : Attunement,
+    pub effects : Vec<Effect>,
+}
@@ -0,0 +1,68 @@
+use super::Speed;
+
+/// The ways a character can move, each of which can have its own speed
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum MovementMode { Walk, Fly, Swim, Climb, Burrow }
+
+/// The speeds of a character in feet per round. A speed of 0 means the character cannot move that way.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct Speeds {
+    pub walk : Speed,
+    pub fly : Speed,
+    pub swim : Speed,
+    pub climb : Speed,
+    pub burrow : Speed,
+    /// Whether the character stays aloft while flying even if they cannot move
+    pub hover : bool,
+}
+
+/// How a class feature or a feat changes the speeds of a character
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum SpeedEffect {
+    /// Increases the speed of a movement mode the character already has
+    Bonus(MovementMode, Speed),
+    /// Lets the character move in a new way, unless they are already faster at it
+    Grant(MovementMode, Speed),
+    /// Lets the character move in a new way as fast as they walk
+    EqualToWalking(MovementMode),
+    Hover,
+}
+
+impl MovementMode {
+    pub fn modes() -> [MovementMode; 5] {
+        [MovementMode::Walk, MovementMode::Fly, MovementMode::Swim, MovementMode::Climb, MovementMode::Burrow]
+    }
+}
+
+impl Speeds {
+    /// Returns the speeds of a creature that can only walk
+    pub fn walking(speed : Speed) -> Self {
+        Speeds { walk : speed, ..Speeds::default() }
+    }
+    pub fn get(&self, mode : &MovementMode) -> Speed {
+        match mode {
+            MovementMode::Walk => self.walk,
+            MovementMode::Fly => self.fly,
+            MovementMode::Swim => self.swim,
+            MovementMode::Climb => self.climb,
+            MovementMode::Burrow => self.burrow,
+        }
+    }
+    pub fn get_mut(&mut self, mode : &MovementMode) -> &mut Speed {
+        match mode {
+            MovementMode::Walk => &mut self.walk,
+            MovementMode::Fly => &mut self.fly,
+            MovementMode::Swim => &mut self.swim,
+            MovementMode::Climb => &mut self.climb,
+            MovementMode::Burrow => &mut self.burrow,
+        }
+    }
+    /// Applies a change to the speed of every movement mode
+    pub fn map(&self, change : impl Fn(Speed) -> Speed) -> Self {
+        let mut speeds = *self;
+        for mode in MovementMode::modes() {
+            *speeds.get_mut(&mode) = change(self.get(&mode));
+        }
+        speeds
+    }
+}
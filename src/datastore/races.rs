@@ -1,4 +1,4 @@
-use super::{ AbilityArray, Skill, CombatProficiency, Speed, Size };
+use super::{ Ability, AbilityArray, Skill, SaveEffect, CombatProficiency, Speeds, Size, Die, DamageType, DamageDefense };
 use std::collections::HashMap;
 
 pub struct Race {
@@ -6,10 +6,12 @@ pub struct Race {
     pub long_text : String,
     pub ability_bonuses : AbilityArray,
     pub size : Size,
-    pub speed : Speed,
+    pub speed : Speeds,
     pub languages: Vec<String>,
     pub skill_proficiencies : Vec<Skill>,
     pub combat_proficiencies : Vec<CombatProficiency>,
+    pub save_effects : Vec<SaveEffect>,
+    pub traits : Vec<Trait>,
     pub subraces : HashMap<String, Subrace>,
 }
 
@@ -20,6 +22,38 @@ pub struct Subrace {
     pub languages : Vec<String>,
     pub skill_proficiencies : Vec<Skill>,
     pub combat_proficiencies : Vec<CombatProficiency>,
+    pub save_effects : Vec<SaveEffect>,
+    pub traits : Vec<Trait>,
+}
+
+/// Racial traits beyond ability scores and proficiencies
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Trait {
+    /// The range of darkvision in feet
+    Darkvision(u16),
+    DamageDefense(DamageDefense, DamageType),
+    /// The name of a condition that cannot affect the character
+    ConditionImmunity(String),
+    InnateSpell(InnateSpell),
+    NaturalWeapon(NaturalWeapon),
+}
+
+/// A spell the character can cast without a spell slot a number of times per long rest
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct InnateSpell {
+    pub spell : String,
+    pub ability : Ability,
+    /// The character level from which on the spell can be cast
+    pub level : i8,
+    pub uses_per_day : u8,
+}
+
+/// Claws, fangs, horns and the like, which a character always has at hand
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct NaturalWeapon {
+    pub name : String,
+    pub damage_die : Die,
+    pub damage_type : DamageType,
 }
 
 impl Race {
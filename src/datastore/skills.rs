@@ -1,7 +1,21 @@
-use super::{ WeaponCategory, ArmorCategory };
+use super::{ Ability, WeaponCategory, ArmorCategory };
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
-pub enum SkillLevel { None, Proficient, Expert }
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub enum SkillLevel { None, HalfProficient(Rounding), Proficient, Expert }
+/// Whether half of the proficiency bonus is rounded down or up
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub enum Rounding { Down, Up }
+
+/// Something that changes the saving throws of a character
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum SaveEffect {
+    Proficiency(Ability),
+    /// A bonus to all saving throws, like that of an aura
+    Bonus(i8),
+    /// Advantage on saving throws with the ability, or with every ability if None,
+    /// against a kind of threat like "poison", or against everything if None
+    Advantage(Option<Ability>, Option<String>),
+}
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum CombatProficiency{
@@ -30,30 +44,26 @@ pub enum Skill {
     Intimidation,
     Performance,
     Persuasion,
-    AlchemistTools,
-    BrewerTools,
-    CalligrapherTools,
-    CarpenterTools,
-    CartographerTools,
-    CobblerTools,
-    CookTools,
-    GlassblowerTools,
-    JewelerTools,
-    LeatherworkerTools,
-    MasonTools,
-    PainterTools,
-    PotterTools,
-    SmithTools,
-    TinkerTools,
-    WeaverTools,
-    WoodcarverTools,
-    DisguiseTools,
-    ForgeryTools,
-    HerbalistTools,
-    NavigatorTools,
-    PoisonerTools,
-    ThievesTools,
-    GamingTools(String),
-    MusicalInstrument(String),
-    Vehicle(String),
-}
\ No newline at end of file
+}
+
+impl Skill {
+    /// Returns all skills in the order they are listed on the character sheet
+    pub fn skills() -> Vec<Skill> {
+        vec![
+            Skill::Athletics, Skill::Acrobatics, Skill::SleightOfHand, Skill::Stealth,
+            Skill::Arcana, Skill::History, Skill::Investigation, Skill::Nature, Skill::Religion,
+            Skill::AnimalHandling, Skill::Insight, Skill::Medicine, Skill::Perception, Skill::Survival,
+            Skill::Deception, Skill::Intimidation, Skill::Performance, Skill::Persuasion,
+        ]
+    }
+    /// Returns the ability checks with the skill use, unless the game master calls for another one
+    pub fn default_ability(&self) -> Ability {
+        match self {
+            Skill::Athletics => Ability::Str,
+            Skill::Acrobatics | Skill::SleightOfHand | Skill::Stealth => Ability::Dex,
+            Skill::Arcana | Skill::History | Skill::Investigation | Skill::Nature | Skill::Religion => Ability::Int,
+            Skill::AnimalHandling | Skill::Insight | Skill::Medicine | Skill::Perception | Skill::Survival => Ability::Wis,
+            Skill::Deception | Skill::Intimidation | Skill::Performance | Skill::Persuasion => Ability::Cha,
+        }
+    }
+}
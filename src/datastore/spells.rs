@@ -1,9 +1,13 @@
 use std::collections::{ HashMap, HashSet };
 use std::iter::FromIterator;
+use crate::error::Error;
+use super::{ DamageType, Die, Reach };
 
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum SpellCaster { None, Third, Half, Full, Pact, Custom(String) }
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
-pub enum SpellCaster { None, Third, Half, Full }
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum SlotRecovery { ShortRest, LongRest }
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
 pub enum SpellLevel { Cantrip, First, Second, Third, Fourth, Fifth, Sixth, Seventh, Eighth, Ninth }
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum SpellComponent { Verbal, Somatic, Material(String) }
@@ -19,6 +23,31 @@ pub struct Spell {
     pub casting_time : String,
     pub components : HashSet<SpellComponent>,
     pub duration : String,
+    /// The type of the damage the spell deals, if any
+    pub damage_type : Option<DamageType>,
+    /// The spell attack the spell makes, if any
+    pub attack : Option<SpellAttack>,
+}
+
+/// A spell attack roll made as part of casting a spell
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct SpellAttack {
+    /// The range of the attack in feet
+    pub range : Reach,
+    /// The number of damage dice rolled on a hit. Cantrips roll more of them as the character levels up.
+    pub damage_dice : u8,
+    pub damage_die : Die,
+}
+
+/// The spell slots a type of caster has at each of their class levels
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SpellProgression {
+    pub recovery : SlotRecovery,
+    /// How many class levels make up one caster level when the levels of several classes
+    /// are combined into the shared slot table, or None if the slots are never shared
+    pub multiclass_divisor : Option<i8>,
+    /// The spell slots at each class level, starting with level 1
+    pub slots : Vec<SpellSlots>,
 }
 
 impl SpellLevel {
@@ -34,78 +63,97 @@ impl SpellLevel {
             )
         )
     }
-    pub fn slots_for_level(level : &i8, caster : &SpellCaster) -> SpellSlots {
-        match caster {
-            SpellCaster::None => SpellLevel::slots(0,0,0,0,0,0,0,0,0),
-            SpellCaster::Full => match level {
-                1 => SpellLevel::slots(2,0,0,0,0,0,0,0,0),
-                2 => SpellLevel::slots(3,0,0,0,0,0,0,0,0),
-                3 => SpellLevel::slots(4,2,0,0,0,0,0,0,0),
-                4 => SpellLevel::slots(4,3,0,0,0,0,0,0,0),
-                5 => SpellLevel::slots(4,3,2,0,0,0,0,0,0),
-                6 => SpellLevel::slots(4,3,3,0,0,0,0,0,0),
-                7 => SpellLevel::slots(4,3,3,1,0,0,0,0,0),
-                8 => SpellLevel::slots(4,3,3,2,0,0,0,0,0),
-                9 => SpellLevel::slots(4,3,3,3,1,0,0,0,0),
-                10 => SpellLevel::slots(4,3,3,3,2,0,0,0,0),
-                11 => SpellLevel::slots(4,3,3,3,2,1,0,0,0),
-                12 => SpellLevel::slots(4,3,3,3,2,1,0,0,0),
-                13 => SpellLevel::slots(4,3,3,3,2,1,1,0,0),
-                14 => SpellLevel::slots(4,3,3,3,2,1,1,0,0),
-                15 => SpellLevel::slots(4,3,3,3,2,1,1,1,0),
-                16 => SpellLevel::slots(4,3,3,3,2,1,1,1,0),
-                17 => SpellLevel::slots(4,3,3,3,2,1,1,1,1),
-                18 => SpellLevel::slots(4,3,3,3,3,1,1,1,1),
-                19 => SpellLevel::slots(4,3,3,3,3,2,1,1,1),
-                20 => SpellLevel::slots(4,3,3,3,3,2,2,1,1),
-                _ => panic!("Invalid level")
-            },
-            SpellCaster::Half => match level {
-                1 => SpellLevel::slots(0,0,0,0,0,0,0,0,0),
-                2 => SpellLevel::slots(2,0,0,0,0,0,0,0,0),
-                3 => SpellLevel::slots(3,0,0,0,0,0,0,0,0),
-                4 => SpellLevel::slots(3,0,0,0,0,0,0,0,0),
-                5 => SpellLevel::slots(4,2,0,0,0,0,0,0,0),
-                6 => SpellLevel::slots(4,2,0,0,0,0,0,0,0),
-                7 => SpellLevel::slots(4,3,0,0,0,0,0,0,0),
-                8 => SpellLevel::slots(4,3,0,0,0,0,0,0,0),
-                9 => SpellLevel::slots(4,3,2,0,0,0,0,0,0),
-                10 => SpellLevel::slots(4,3,2,0,0,0,0,0,0),
-                11 => SpellLevel::slots(4,3,3,0,0,0,0,0,0),
-                12 => SpellLevel::slots(4,3,3,0,0,0,0,0,0),
-                13 => SpellLevel::slots(4,3,3,1,0,0,0,0,0),
-                14 => SpellLevel::slots(4,3,3,1,0,0,0,0,0),
-                15 => SpellLevel::slots(4,3,3,2,0,0,0,0,0),
-                16 => SpellLevel::slots(4,3,3,2,0,0,0,0,0),
-                17 => SpellLevel::slots(4,3,3,3,1,0,0,0,0),
-                18 => SpellLevel::slots(4,3,3,3,1,0,0,0,0),
-                19 => SpellLevel::slots(4,3,3,3,2,0,0,0,0),
-                20 => SpellLevel::slots(4,3,3,3,2,0,0,0,0),
-                _ => panic!("Invalid level")
-            },
-            SpellCaster::Third => match level {
-                1 => SpellLevel::slots(0,0,0,0,0,0,0,0,0),
-                2 => SpellLevel::slots(0,0,0,0,0,0,0,0,0),
-                3 => SpellLevel::slots(2,0,0,0,0,0,0,0,0),
-                4 => SpellLevel::slots(3,0,0,0,0,0,0,0,0),
-                5 => SpellLevel::slots(3,0,0,0,0,0,0,0,0),
-                6 => SpellLevel::slots(3,0,0,0,0,0,0,0,0),
-                7 => SpellLevel::slots(4,2,0,0,0,0,0,0,0),
-                8 => SpellLevel::slots(4,2,0,0,0,0,0,0,0),
-                9 => SpellLevel::slots(4,2,0,0,0,0,0,0,0),
-                10 => SpellLevel::slots(4,3,0,0,0,0,0,0,0),
-                11 => SpellLevel::slots(4,3,0,0,0,0,0,0,0),
-                12 => SpellLevel::slots(4,3,0,0,0,0,0,0,0),
-                13 => SpellLevel::slots(4,3,2,0,0,0,0,0,0),
-                14 => SpellLevel::slots(4,3,2,0,0,0,0,0,0),
-                15 => SpellLevel::slots(4,3,2,0,0,0,0,0,0),
-                16 => SpellLevel::slots(4,3,3,0,0,0,0,0,0),
-                17 => SpellLevel::slots(4,3,3,0,0,0,0,0,0),
-                18 => SpellLevel::slots(4,3,3,0,0,0,0,0,0),
-                19 => SpellLevel::slots(4,3,3,1,0,0,0,0,0),
-                20 => SpellLevel::slots(4,3,3,1,0,0,0,0,0),
-                _ => panic!("Invalid level")
-            },
+}
+
+const FULL_CASTER_SLOTS : [[u8; 9]; 20] = [
+    [2,0,0,0,0,0,0,0,0], [3,0,0,0,0,0,0,0,0], [4,2,0,0,0,0,0,0,0], [4,3,0,0,0,0,0,0,0],
+    [4,3,2,0,0,0,0,0,0], [4,3,3,0,0,0,0,0,0], [4,3,3,1,0,0,0,0,0], [4,3,3,2,0,0,0,0,0],
+    [4,3,3,3,1,0,0,0,0], [4,3,3,3,2,0,0,0,0], [4,3,3,3,2,1,0,0,0], [4,3,3,3,2,1,0,0,0],
+    [4,3,3,3,2,1,1,0,0], [4,3,3,3,2,1,1,0,0], [4,3,3,3,2,1,1,1,0], [4,3,3,3,2,1,1,1,0],
+    [4,3,3,3,2,1,1,1,1], [4,3,3,3,3,1,1,1,1], [4,3,3,3,3,2,1,1,1], [4,3,3,3,3,2,2,1,1],
+];
+const HALF_CASTER_SLOTS : [[u8; 9]; 20] = [
+    [0,0,0,0,0,0,0,0,0], [2,0,0,0,0,0,0,0,0], [3,0,0,0,0,0,0,0,0], [3,0,0,0,0,0,0,0,0],
+    [4,2,0,0,0,0,0,0,0], [4,2,0,0,0,0,0,0,0], [4,3,0,0,0,0,0,0,0], [4,3,0,0,0,0,0,0,0],
+    [4,3,2,0,0,0,0,0,0], [4,3,2,0,0,0,0,0,0], [4,3,3,0,0,0,0,0,0], [4,3,3,0,0,0,0,0,0],
+    [4,3,3,1,0,0,0,0,0], [4,3,3,1,0,0,0,0,0], [4,3,3,2,0,0,0,0,0], [4,3,3,2,0,0,0,0,0],
+    [4,3,3,3,1,0,0,0,0], [4,3,3,3,1,0,0,0,0], [4,3,3,3,2,0,0,0,0], [4,3,3,3,2,0,0,0,0],
+];
+const THIRD_CASTER_SLOTS : [[u8; 9]; 20] = [
+    [0,0,0,0,0,0,0,0,0], [0,0,0,0,0,0,0,0,0], [2,0,0,0,0,0,0,0,0], [3,0,0,0,0,0,0,0,0],
+    [3,0,0,0,0,0,0,0,0], [3,0,0,0,0,0,0,0,0], [4,2,0,0,0,0,0,0,0], [4,2,0,0,0,0,0,0,0],
+    [4,2,0,0,0,0,0,0,0], [4,3,0,0,0,0,0,0,0], [4,3,0,0,0,0,0,0,0], [4,3,0,0,0,0,0,0,0],
+    [4,3,2,0,0,0,0,0,0], [4,3,2,0,0,0,0,0,0], [4,3,2,0,0,0,0,0,0], [4,3,3,0,0,0,0,0,0],
+    [4,3,3,0,0,0,0,0,0], [4,3,3,0,0,0,0,0,0], [4,3,3,1,0,0,0,0,0], [4,3,3,1,0,0,0,0,0],
+];
+const PACT_CASTER_SLOTS : [[u8; 9]; 20] = [
+    [1,0,0,0,0,0,0,0,0], [2,0,0,0,0,0,0,0,0], [0,2,0,0,0,0,0,0,0], [0,2,0,0,0,0,0,0,0],
+    [0,0,2,0,0,0,0,0,0], [0,0,2,0,0,0,0,0,0], [0,0,0,2,0,0,0,0,0], [0,0,0,2,0,0,0,0,0],
+    [0,0,0,0,2,0,0,0,0], [0,0,0,0,2,0,0,0,0], [0,0,0,0,3,0,0,0,0], [0,0,0,0,3,0,0,0,0],
+    [0,0,0,0,3,0,0,0,0], [0,0,0,0,3,0,0,0,0], [0,0,0,0,3,0,0,0,0], [0,0,0,0,3,0,0,0,0],
+    [0,0,0,0,4,0,0,0,0], [0,0,0,0,4,0,0,0,0], [0,0,0,0,4,0,0,0,0], [0,0,0,0,4,0,0,0,0],
+];
+
+impl SpellProgression {
+    /// Creates a progression from a table with one row of slot counts (first to ninth level) per class level
+    pub fn from_table(recovery : SlotRecovery, multiclass_divisor : Option<i8>, table : &[[u8; 9]]) -> Self {
+        SpellProgression {
+            recovery,
+            multiclass_divisor,
+            slots : table.iter().map( |row| SpellLevel::slots(row[0], row[1], row[2], row[3], row[4], row[5], row[6], row[7], row[8]) ).collect(),
         }
     }
-}
\ No newline at end of file
+    pub fn full() -> Self { SpellProgression::from_table(SlotRecovery::LongRest, Some(1), &FULL_CASTER_SLOTS) }
+    pub fn half() -> Self { SpellProgression::from_table(SlotRecovery::LongRest, Some(2), &HALF_CASTER_SLOTS) }
+    pub fn third() -> Self { SpellProgression::from_table(SlotRecovery::LongRest, Some(3), &THIRD_CASTER_SLOTS) }
+    /// Pact magic has only a few slots, which are all of the same level and recovered on a short rest
+    pub fn pact() -> Self { SpellProgression::from_table(SlotRecovery::ShortRest, None, &PACT_CASTER_SLOTS) }
+    /// Returns the spell slots at the given class level, or an error if the progression does not cover that level
+    pub fn slots_for_level(&self, level : &i8) -> Result<SpellSlots, Error> {
+        if *level < 1 || *level as usize > self.slots.len() {
+            return Err(Error::InvalidLevel(*level));
+        }
+        Ok(self.slots[(*level - 1) as usize].clone())
+    }
+    /// Returns the long rest spell slots of a character with levels in several classes.
+    /// A single spellcasting class uses its own table, otherwise the levels of all classes
+    /// are combined into one caster level for the shared table. Progressions without a
+    /// multiclass divisor keep their slots separate and add them on top.
+    pub fn multiclass_slots(casters : &[(i8, &SpellProgression)], shared : &SpellProgression) -> Result<SpellSlots, Error> {
+        let (combined, separate) : (Vec<_>, Vec<_>) = casters.iter()
+            .filter( |(_, progression)| progression.recovery == SlotRecovery::LongRest )
+            .partition( |(_, progression)| progression.multiclass_divisor.is_some() );
+        let mut slots = match combined.as_slice() {
+            [] => SpellLevel::slots(0,0,0,0,0,0,0,0,0),
+            [(level, progression)] => progression.slots_for_level(level)?,
+            _ => {
+                let caster_level : i8 = combined.iter()
+                    .filter_map( |(level, progression)| progression.multiclass_divisor.map( |divisor| level / divisor ) )
+                    .sum();
+                if caster_level > 0 {
+                    shared.slots_for_level(&caster_level)?
+                } else {
+                    SpellLevel::slots(0,0,0,0,0,0,0,0,0)
+                }
+            }
+        };
+        for (level, progression) in separate {
+            SpellProgression::add_slots(&mut slots, progression.slots_for_level(&level)?);
+        }
+        Ok(slots)
+    }
+    /// Returns the short rest spell slots of a character with levels in several classes,
+    /// which are kept separate from the shared slots and determined by the levels in each class alone
+    pub fn multiclass_short_rest_slots(casters : &[(i8, &SpellProgression)]) -> Result<SpellSlots, Error> {
+        let mut slots = SpellLevel::slots(0,0,0,0,0,0,0,0,0);
+        for (level, progression) in casters.iter().filter( |(_, progression)| progression.recovery == SlotRecovery::ShortRest ) {
+            SpellProgression::add_slots(&mut slots, progression.slots_for_level(level)?);
+        }
+        Ok(slots)
+    }
+    fn add_slots(slots : &mut SpellSlots, additional : SpellSlots) {
+        for (level, count) in additional {
+            *slots.entry(level).or_insert(0) += count;
+        }
+    }
+}
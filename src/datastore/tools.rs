@@ -0,0 +1,21 @@
+use super::Ability;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ToolCategory { ArtisanTools, GamingSet, MusicalInstrument, Vehicle, Other }
+
+/// Tools, instruments, gaming sets and vehicles a character can be proficient with
+pub struct Tool {
+    pub name : String,
+    pub long_text : String,
+    pub category : ToolCategory,
+    /// The ability that is usually used for checks with the tool
+    pub ability : Ability,
+}
+
+/// A tool proficiency granted by e.g. a class, which can leave the choice of tool to the character
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum ToolProficiency {
+    Tool(String),
+    /// Any one tool of the category, chosen by the character
+    AnyOf(ToolCategory),
+}
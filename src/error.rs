@@ -0,0 +1,60 @@
+use crate::character::ATTUNEMENT_SLOTS;
+use crate::datastore::SpellLevel;
+use std::fmt;
+
+/// The kinds of entries a character can reference in the datastore
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum DataKind { Race, Subrace, Class, Background, Feat, Spell, SpellProgression, Weapon, Armor, Item, MagicItem, Condition, Tool }
+
+/// Everything that can go wrong when building or using a character
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Error {
+    /// An entry referenced by name could not be found in the datastore
+    MissingData { kind : DataKind, name : String },
+    /// The character has not chosen e.g. a race yet, but the operation depends on it
+    NotChosen(DataKind),
+    /// The inventory of the character has no entry with this id
+    UnknownInventoryEntry(usize),
+    /// The character does not meet the prerequisites of the named feat
+    PrerequisitesNotMet { feat : String },
+    /// The level is outside of the range covered by the rules
+    InvalidLevel(i8),
+    /// The character does not have enough money to pay
+    InsufficientFunds,
+    /// The character has no spell slots of the level left
+    NoSpellSlot(SpellLevel),
+    /// The character is already attuned to as many items as they can be
+    AttunementLimit,
+    /// The named item already takes the place the character wants to equip something in
+    AlreadyEquipped(String),
+    /// The named spell or feature has no uses left until the character rests
+    NoUsesLeft(String),
+    /// The operation would leave the character in a state the rules do not allow
+    Validation(String),
+}
+
+impl Error {
+    pub fn missing(kind : DataKind, name : &str) -> Self {
+        Error::MissingData { kind, name : name.to_owned() }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::MissingData { kind, name } => write!(f, "{:?} \"{}\" was not found.", kind, name),
+            Error::NotChosen(kind) => write!(f, "Character has no {:?}.", kind),
+            Error::UnknownInventoryEntry(id) => write!(f, "Inventory has no entry {}.", id),
+            Error::PrerequisitesNotMet { feat } => write!(f, "Character does not meet the prerequisites for the feat \"{}\".", feat),
+            Error::InvalidLevel(level) => write!(f, "Level {} is not valid.", level),
+            Error::InsufficientFunds => write!(f, "Not enough money."),
+            Error::NoSpellSlot(level) => write!(f, "Character has no spell slots of level {:?} left.", level),
+            Error::AttunementLimit => write!(f, "Character cannot be attuned to more than {} items.", ATTUNEMENT_SLOTS),
+            Error::AlreadyEquipped(item) => write!(f, "\"{}\" is already equipped.", item),
+            Error::NoUsesLeft(name) => write!(f, "Character has no uses of \"{}\" left.", name),
+            Error::Validation(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -1,5 +1,6 @@
 mod datastore;
 mod character;
+mod error;
 
 fn main() {
     println!("Hello D&D!");